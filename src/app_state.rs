@@ -1,8 +1,74 @@
-use bevy::state::state::States;
+use bevy::{
+    prelude::*,
+    state::state::{StateSet, States, SubStates},
+};
+
+use crate::{RestartLevel, StartGame, StopGame};
+
+/// Adds the states and moves between them on [`StartGame`], [`StopGame`] and [`RestartLevel`].
+#[derive(Debug)]
+pub struct AppStatePlugin;
+
+impl Plugin for AppStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<AppState>()
+            .add_sub_state::<GameState>()
+            .add_sub_state::<PauseMenuState>();
+
+        app.add_observer(start_game_observer)
+            .add_observer(stop_game_observer)
+            .add_observer(restart_level_observer);
+    }
+}
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash, States)]
 pub enum AppState {
     #[default]
     MainMenu,
+    /// Waits for the current level asset before entering [`AppState::InGame`].
+    LoadingLevel,
     InGame,
+    /// Shows a summary of the cleared level before the next one is loaded.
+    LevelComplete,
+    /// The last level of the campaign was cleared.
+    CampaignWon,
+    /// All lives were lost.
+    GameOver,
+    /// A versus round ended because the level was cleared or a player ran out of lives.
+    RoundOver,
+    /// A networked client waits for the host to answer its handshake.
+    Connecting,
+    /// A networked client plays in the game of the host.
+    RemoteGame,
+}
+
+/// Only exists while in [`AppState::InGame`]. Gameplay systems only run while [`GameState::Running`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, SubStates)]
+#[source(AppState = AppState::InGame)]
+pub enum GameState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// The page of the pause menu that is shown.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, SubStates)]
+#[source(GameState = GameState::Paused)]
+pub enum PauseMenuState {
+    #[default]
+    Main,
+    Settings,
+    Controls,
+}
+
+fn start_game_observer(_: Trigger<StartGame>, mut app_state: ResMut<NextState<AppState>>) {
+    app_state.set(AppState::LoadingLevel);
+}
+
+fn stop_game_observer(_: Trigger<StopGame>, mut app_state: ResMut<NextState<AppState>>) {
+    app_state.set(AppState::MainMenu);
+}
+
+fn restart_level_observer(_: Trigger<RestartLevel>, mut app_state: ResMut<NextState<AppState>>) {
+    app_state.set(AppState::LoadingLevel);
 }
@@ -0,0 +1,131 @@
+//! The command line arguments. See the README for what they do.
+
+use std::{
+    iter::Peekable,
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+};
+
+use thiserror::Error;
+
+use crate::{
+    net::{self, DEFAULT_PORT, NetMode},
+    replay::ReplayMode,
+};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Args {
+    pub net_mode: NetMode,
+    pub replay_mode: ReplayMode,
+    /// Run without a window. Joins with the scripted client or plays the replay back as fast as
+    /// possible.
+    pub headless: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum ArgsError {
+    #[error("Unknown argument {0}")]
+    Unknown(String),
+    #[error("{0} is not a valid port")]
+    InvalidPort(String),
+    #[error("Could not resolve the address {0}")]
+    InvalidAddress(String),
+    #[error("{0} needs a file")]
+    MissingPath(&'static str),
+    #[error("{0} and {1} can not be used together")]
+    Conflict(&'static str, &'static str),
+    #[error("--headless needs --join or --replay")]
+    HeadlessWithoutJoinOrReplay,
+}
+
+impl Args {
+    /// Parses the command line arguments, without the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut args = args.into_iter().peekable();
+        let mut parsed = Args::default();
+
+        while let Some(arg) = args.next() {
+            // Values are optional, so the next argument only belongs to an option if it is not an
+            // option itself.
+            match arg.as_str() {
+                "--host" => {
+                    let port = match args.next_if(|next| !next.starts_with("--")) {
+                        Some(port) => port.parse().map_err(|_| ArgsError::InvalidPort(port))?,
+                        None => DEFAULT_PORT,
+                    };
+                    parsed.set_net_mode(NetMode::Host { port })?;
+                }
+                "--join" => {
+                    let host = match args.next_if(|next| !next.starts_with("--")) {
+                        Some(address) => net::resolve(&address)?,
+                        None => SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_PORT)),
+                    };
+                    parsed.set_net_mode(NetMode::Join { host })?;
+                }
+                "--headless" => parsed.headless = true,
+                "--record" => {
+                    let path = path_value(&mut args, "--record")?;
+                    parsed.set_replay_mode(ReplayMode::Record(path))?;
+                }
+                "--replay" => {
+                    let path = path_value(&mut args, "--replay")?;
+                    parsed.set_replay_mode(ReplayMode::Play(path))?;
+                }
+                _ => return Err(ArgsError::Unknown(arg)),
+            };
+        }
+
+        parsed.check_conflicts()?;
+        Ok(parsed)
+    }
+
+    fn set_net_mode(&mut self, net_mode: NetMode) -> Result<(), ArgsError> {
+        if self.net_mode != NetMode::Local {
+            return Err(ArgsError::Conflict("--host", "--join"));
+        };
+
+        self.net_mode = net_mode;
+        Ok(())
+    }
+
+    fn set_replay_mode(&mut self, replay_mode: ReplayMode) -> Result<(), ArgsError> {
+        if self.replay_mode != ReplayMode::Off {
+            return Err(ArgsError::Conflict("--record", "--replay"));
+        };
+
+        self.replay_mode = replay_mode;
+        Ok(())
+    }
+
+    /// The client does not simulate anything, and a replay has no room for a remote player. Without
+    /// a window there is nobody to play, so only the scripted client and playback run headless.
+    fn check_conflicts(&self) -> Result<(), ArgsError> {
+        match (&self.net_mode, &self.replay_mode) {
+            (NetMode::Join { .. }, ReplayMode::Record(_)) => {
+                Err(ArgsError::Conflict("--join", "--record"))
+            }
+            (NetMode::Join { .. }, ReplayMode::Play(_)) => {
+                Err(ArgsError::Conflict("--join", "--replay"))
+            }
+            (NetMode::Host { .. }, ReplayMode::Play(_)) => {
+                Err(ArgsError::Conflict("--host", "--replay"))
+            }
+            (NetMode::Local | NetMode::Host { .. }, ReplayMode::Off | ReplayMode::Record(_))
+                if self.headless =>
+            {
+                Err(ArgsError::HeadlessWithoutJoinOrReplay)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The file that follows `option`.
+fn path_value(
+    args: &mut Peekable<impl Iterator<Item = String>>,
+    option: &'static str,
+) -> Result<PathBuf, ArgsError> {
+    args.next_if(|next| !next.starts_with("--"))
+        .map(PathBuf::from)
+        .ok_or(ArgsError::MissingPath(option))
+}
@@ -1,24 +1,76 @@
-use avian2d::prelude::{Collider, Friction, GravityScale, LinearVelocity, Restitution, RigidBody};
-use bevy::{prelude::*, window::PrimaryWindow};
+use std::time::Duration;
 
-use crate::app_state::AppState;
+use avian2d::prelude::{
+    Collider, CollisionEventsEnabled, CollisionStarted, Friction, GravityScale, LinearVelocity,
+    Restitution, RigidBody,
+};
+use bevy::prelude::*;
+use rand::Rng;
 
-const BALL_RADIUS: f32 = 16.0;
-const MAX_SPEED: f32 = 600.0;
+use crate::{
+    StartGame,
+    app_state::AppState,
+    game_mode::GameMode,
+    input::PlayerInputs,
+    lives::{BallLost, LifeLost},
+    player::{PaddleVelocity, Player, PlayerSize, SpawnPlayers},
+    simulation::{GameRng, SimulationSet},
+    world::{
+        Playfield, TopBorder,
+        block::Block,
+        level::{CurrentLevel, DEFAULT_BALL_SPEED, DEFAULT_MAX_BALL_SPEED, Level},
+    },
+};
+
+pub const BALL_RADIUS: f32 = 16.0;
+const DEFAULT_MAX_BALLS: usize = 8;
+/// Angle between balls that split from the same ball.
+const SPLIT_ANGLE: f32 = 0.3;
+const DEFAULT_SERVE_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_STUCK_TIMEOUT: Duration = Duration::from_secs(8);
 
 pub struct BallPlugin;
 
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<BallSpeedFactor>()
+            .init_resource::<MaxBalls>()
+            .init_resource::<SpeedRamp>()
+            .init_resource::<AngleGuard>()
+            .init_resource::<StuckDetector>()
+            .init_resource::<DeflectionConfig>()
+            .init_resource::<ServeConfig>()
+            .init_resource::<ServingPlayer>();
+
         app.add_observer(spawn_ball_observer)
-            .add_observer(initial_velocity_observer);
+            .add_observer(respawn_ball_observer)
+            .add_observer(reset_serving_player_observer);
 
-        app.add_systems(OnEnter(AppState::MainMenu), despawn_balls_system)
+        app.add_systems(OnExit(AppState::InGame), despawn_balls_system)
             .add_systems(Startup, setup)
-            .add_systems(OnEnter(AppState::InGame), spawn_ball_system)
             .add_systems(
-                Update,
-                (check_ball_death, hold_speed_system).run_if(in_state(AppState::InGame)),
+                OnEnter(AppState::InGame),
+                (
+                    spawn_ball_system.after(SpawnPlayers),
+                    reset_speed_factor_system,
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    check_ball_death,
+                    (
+                        ramp_speed_on_collision_system,
+                        ramp_speed_over_time_system,
+                        (reset_stuck_timer_system, nudge_stuck_balls_system).chain(),
+                        deflect_off_paddle_system,
+                        track_last_touch_system,
+                        hold_speed_system,
+                    )
+                        .chain(),
+                    (follow_paddle_system, release_held_balls_system).chain(),
+                )
+                    .in_set(SimulationSet::Balls),
             );
     }
 }
@@ -33,8 +85,190 @@ struct BallHandles {
 #[derive(Debug, Component)]
 pub struct Ball;
 
+/// A ball resting on a paddle. It follows the paddle until the timer finishes.
+#[derive(Debug, Component)]
+pub struct Held {
+    pub paddle: Entity,
+    /// Horizontal offset from the paddle center.
+    pub offset: f32,
+    pub release: Timer,
+}
+
+/// The player whose paddle last touched or served the ball. Blocks the ball breaks score for them.
+#[derive(Debug, Clone, Component)]
+pub struct LastTouched(pub Player);
+
+/// A ball waiting on the serving paddle for its player to launch it.
+#[derive(Debug, Component)]
+pub struct Serving;
+
+/// The player whose paddle the next new ball rests on. Alternates between the players of the
+/// [`GameMode`] after every lost life. In versus the player who lost the life serves.
+#[derive(Debug, Resource)]
+pub struct ServingPlayer(pub Player);
+
+#[derive(Debug, Resource)]
+pub struct ServeConfig {
+    /// The ball launches on its own if the player did not launch it in time.
+    pub timeout: Duration,
+}
+
+/// How fast a ball flies. New balls, including the one served after a lost life, start at the
+/// level's `ball_speed` and speed up until they reach its `max_ball_speed`.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct BallSpeed {
+    pub current: f32,
+    pub max: f32,
+}
+
+/// How much a ball speeds up during play.
+#[derive(Debug, Resource)]
+pub struct SpeedRamp {
+    /// Added every time the ball hits a block.
+    pub per_hit: f32,
+    /// Added every second the ball is in flight.
+    pub per_second: f32,
+    /// Added every time the ball touches the [`TopBorder`].
+    pub top_border: f32,
+}
+
+/// Keeps balls from flying almost straight sideways or straight up and down. Both values are the
+/// smallest share of the ball speed that goes into that direction.
+#[derive(Debug, Resource)]
+pub struct AngleGuard {
+    pub min_vertical: f32,
+    pub min_horizontal: f32,
+}
+
+/// Nudges balls that have not touched a block or paddle for `timeout` into a new direction.
+#[derive(Debug, Resource)]
+pub struct StuckDetector {
+    pub timeout: Duration,
+    /// The most the ball gets turned, in radians. It always turns at least half as far.
+    pub nudge_angle: f32,
+}
+
+/// How long ago the ball last touched a block or paddle.
+#[derive(Debug, Default, Component)]
+struct SinceContact(Duration);
+
+/// Scales the speed of every ball.
+#[derive(Debug, Resource)]
+pub struct BallSpeedFactor(pub f32);
+
+/// The most balls that can be in play at once.
+#[derive(Debug, Resource)]
+pub struct MaxBalls(pub usize);
+
 #[derive(Debug, Event)]
-struct SpawnBallEvent;
+pub enum SpawnBallEvent {
+    /// Spawns a ball resting on the serving player's paddle.
+    New,
+    /// Splits `ball` into `count` balls flying apart at diverging angles.
+    Split { ball: Entity, count: usize },
+}
+
+/// How players aim the ball with their paddle. The ball leaves straight from the paddle center and
+/// at `max_angle` from the edges.
+#[derive(Debug, Resource)]
+pub struct DeflectionConfig {
+    /// Largest angle between the bounced ball and the vertical, in radians.
+    pub max_angle: f32,
+    /// How much of the paddle's horizontal velocity carries over to the ball. `0.0` turns it off.
+    pub paddle_velocity_influence: f32,
+}
+
+impl Default for BallSpeedFactor {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl BallSpeed {
+    fn increase(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+}
+
+impl AngleGuard {
+    /// Turns `direction` just far enough to respect both minimums.
+    fn apply(&self, direction: Vec2) -> Vec2 {
+        let sign = |value: f32| if value < 0.0 { -1.0 } else { 1.0 };
+
+        let mut direction = direction;
+        if direction.y.abs() < self.min_vertical {
+            direction = Vec2::new(
+                sign(direction.x) * (1.0 - self.min_vertical.powi(2)).sqrt(),
+                sign(direction.y) * self.min_vertical,
+            );
+        };
+        if direction.x.abs() < self.min_horizontal {
+            direction = Vec2::new(
+                sign(direction.x) * self.min_horizontal,
+                sign(direction.y) * (1.0 - self.min_horizontal.powi(2)).sqrt(),
+            );
+        };
+
+        direction
+    }
+}
+
+impl Default for AngleGuard {
+    fn default() -> Self {
+        Self {
+            min_vertical: 0.25,
+            min_horizontal: 0.05,
+        }
+    }
+}
+
+impl Default for StuckDetector {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_STUCK_TIMEOUT,
+            nudge_angle: 0.5,
+        }
+    }
+}
+
+impl Default for SpeedRamp {
+    fn default() -> Self {
+        Self {
+            per_hit: 4.0,
+            per_second: 2.0,
+            top_border: 40.0,
+        }
+    }
+}
+
+impl Default for DeflectionConfig {
+    fn default() -> Self {
+        Self {
+            max_angle: 60f32.to_radians(),
+            paddle_velocity_influence: 0.2,
+        }
+    }
+}
+
+impl Default for ServingPlayer {
+    fn default() -> Self {
+        Self(Player::First)
+    }
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_SERVE_TIMEOUT,
+        }
+    }
+}
+
+impl Default for MaxBalls {
+    fn default() -> Self {
+        Self(DEFAULT_MAX_BALLS)
+    }
+}
 
 fn setup(
     mut commands: Commands,
@@ -50,30 +284,155 @@ fn setup(
 }
 
 fn spawn_ball_system(mut commands: Commands) {
-    commands.trigger(SpawnBallEvent);
+    commands.trigger(SpawnBallEvent::New);
 }
 
 fn spawn_ball_observer(
-    _: Trigger<SpawnBallEvent>,
-    windows: Query<&Window, With<PrimaryWindow>>,
+    trigger: Trigger<SpawnBallEvent>,
+    playfield: Res<Playfield>,
     mut commands: Commands,
     ball_handles: Res<BallHandles>,
+    max_balls: Res<MaxBalls>,
+    serve_config: Res<ServeConfig>,
+    serving_player: Res<ServingPlayer>,
+    mut rng: ResMut<GameRng>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<Level>>,
+    balls: Query<
+        (
+            &Transform,
+            &LinearVelocity,
+            &BallSpeed,
+            Option<&LastTouched>,
+        ),
+        With<Ball>,
+    >,
+    paddles: Query<(Entity, &Player)>,
 ) -> Result {
-    let size = windows.single()?.size();
+    let free = max_balls.0.saturating_sub(balls.iter().count());
+    let base_speed = current_level
+        .and_then(|current_level| levels.get(&current_level.0))
+        .map_or(
+            BallSpeed {
+                current: DEFAULT_BALL_SPEED,
+                max: DEFAULT_MAX_BALL_SPEED,
+            },
+            |level| BallSpeed {
+                current: level.ball_speed,
+                max: level.max_ball_speed,
+            },
+        );
 
-    commands.spawn((
+    match *trigger.event() {
+        SpawnBallEvent::New => {
+            if free == 0 {
+                debug!("Ball limit of {} reached", max_balls.0);
+                return Ok(());
+            };
+
+            let mut ball = commands.spawn((
+                ball(&ball_handles),
+                base_speed,
+                Transform::from_xyz(0.0, (-playfield.half_size().y / 8.0) * 6.0, 0.0),
+                LinearVelocity::ZERO,
+            ));
+
+            // Without a paddle to rest on the ball launches right away.
+            let paddle = paddles
+                .iter()
+                .find(|(_, player)| **player == serving_player.0)
+                .or_else(|| paddles.iter().next());
+            match paddle {
+                Some((paddle, player)) => {
+                    ball.insert((
+                        Serving,
+                        LastTouched(player.clone()),
+                        Held {
+                            paddle,
+                            offset: 0.0,
+                            release: Timer::new(serve_config.timeout, TimerMode::Once),
+                        },
+                    ));
+                }
+                None => {
+                    ball.insert(LinearVelocity(
+                        Vec2::new(rng.random_range(-3.5..3.5), 1.0).normalize()
+                            * base_speed.current,
+                    ));
+                }
+            };
+        }
+        SpawnBallEvent::Split {
+            ball: source,
+            count,
+        } => {
+            let Ok((transform, velocity, speed, last_touched)) = balls.get(source) else {
+                return Ok(());
+            };
+
+            let direction = velocity.normalize_or(Vec2::Y);
+            // The source ball keeps flying, the new balls alternate left and right of it.
+            for i in 1..count.min(free + 1) {
+                let side = if i % 2 == 1 { 1.0 } else { -1.0 };
+                let angle = side * SPLIT_ANGLE * i.div_ceil(2) as f32;
+
+                let mut split = commands.spawn((
+                    ball(&ball_handles),
+                    *speed,
+                    *transform,
+                    LinearVelocity(Vec2::from_angle(angle).rotate(direction) * velocity.length()),
+                ));
+                if let Some(last_touched) = last_touched {
+                    split.insert(last_touched.clone());
+                };
+            }
+        }
+    };
+
+    Ok(())
+}
+
+fn ball(ball_handles: &BallHandles) -> impl Bundle {
+    (
         Ball,
         Mesh2d(ball_handles.mesh_handle.clone()),
         MeshMaterial2d(ball_handles.material_handle.clone()),
-        Transform::from_xyz(0.0, (-size.y / 2.0 / 8.0) * 6.0, 0.0),
         RigidBody::Dynamic,
+        SinceContact::default(),
         Collider::circle(BALL_RADIUS),
+        CollisionEventsEnabled,
         Restitution::new(1.0),
         GravityScale(0.0),
         Friction::new(0.0),
-    ));
+    )
+}
 
-    Ok(())
+fn respawn_ball_observer(
+    trigger: Trigger<LifeLost>,
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    mut serving_player: ResMut<ServingPlayer>,
+) {
+    if trigger.remaining == 0 {
+        return;
+    };
+
+    serving_player.0 = match &trigger.player {
+        Some(player) => player.clone(),
+        None => {
+            let players = game_mode.players();
+            let next = players
+                .iter()
+                .position(|player| *player == serving_player.0)
+                .map_or(0, |current| (current + 1) % players.len());
+            players[next].clone()
+        }
+    };
+    commands.trigger(SpawnBallEvent::New);
+}
+
+fn reset_serving_player_observer(_: Trigger<StartGame>, mut serving_player: ResMut<ServingPlayer>) {
+    *serving_player = ServingPlayer::default();
 }
 
 fn despawn_balls_system(mut commands: Commands, balls: Query<Entity, With<Ball>>) {
@@ -82,39 +441,237 @@ fn despawn_balls_system(mut commands: Commands, balls: Query<Entity, With<Ball>>
     }
 }
 
-fn initial_velocity_observer(trigger: Trigger<OnAdd, Ball>, mut commands: Commands) {
-    commands.entity(trigger.target()).insert(LinearVelocity(
-        Vec2::new(rand::random_range(-3.5..3.5), 1.0).normalize() * MAX_SPEED,
-    ));
-}
-
 fn check_ball_death(
     mut commands: Commands,
     ball_handles: Res<BallHandles>,
-    windows: Query<&Window, With<PrimaryWindow>>,
+    game_mode: Res<GameMode>,
+    playfield: Res<Playfield>,
     balls: Query<(Entity, &Transform), With<Ball>>,
-) -> Result {
+) {
     use bevy::audio::Volume;
 
-    let y = windows.single()?.height();
-    let half_y = y / 2.0;
+    let half_y = playfield.half_size().y;
 
+    let mut remaining = balls.iter().count();
     for (entity, transform) in balls.iter() {
-        if transform.translation.y + BALL_RADIUS < -half_y {
+        let below = transform.translation.y + BALL_RADIUS < -half_y;
+        // Without a top border the ball can also leave the field through the top edge.
+        let above = game_mode.is_split() && transform.translation.y - BALL_RADIUS > half_y;
+        if below || above {
             commands.spawn((
                 AudioPlayer::new(ball_handles.ball_death_sound_handle.clone()),
                 PlaybackSettings::DESPAWN.with_volume(Volume::Linear(0.50)),
             ));
             commands.entity(entity).despawn();
-            commands.trigger(SpawnBallEvent);
+            remaining -= 1;
+
+            // A life is only lost once the last ball is gone.
+            if remaining == 0 {
+                commands.trigger(BallLost {
+                    defender: game_mode.defender(transform.translation.truncate()),
+                });
+            };
         }
     }
+}
 
-    Ok(())
+fn deflect_off_paddle_system(
+    config: Res<DeflectionConfig>,
+    player_size: Res<PlayerSize>,
+    mut collision_started: EventReader<CollisionStarted>,
+    mut balls: Query<(&Transform, &mut LinearVelocity), (With<Ball>, Without<Held>)>,
+    paddles: Query<(&Transform, &PaddleVelocity), With<Player>>,
+) {
+    for &CollisionStarted(a, b) in collision_started.read() {
+        let (ball, paddle) = match (
+            balls.contains(a) && paddles.contains(b),
+            balls.contains(b) && paddles.contains(a),
+        ) {
+            (true, _) => (a, b),
+            (_, true) => (b, a),
+            _ => continue,
+        };
+
+        let (Ok((ball_transform, mut velocity)), Ok((paddle_transform, paddle_velocity))) =
+            (balls.get_mut(ball), paddles.get(paddle))
+        else {
+            continue;
+        };
+
+        let half_width = player_size.x * paddle_transform.scale.x / 2.0;
+        let offset = ((ball_transform.translation.x - paddle_transform.translation.x) / half_width)
+            .clamp(-1.0, 1.0);
+        // Send the ball away from the side of the paddle it hit.
+        let vertical = if ball_transform.translation.y >= paddle_transform.translation.y {
+            1.0
+        } else {
+            -1.0
+        };
+
+        let speed = velocity.length();
+        let angle = offset * config.max_angle;
+        let aimed = Vec2::new(angle.sin(), angle.cos() * vertical) * speed
+            + Vec2::X * paddle_velocity.x * config.paddle_velocity_influence;
+
+        // The paddle velocity may push the ball past the maximum angle.
+        let angle = aimed
+            .x
+            .atan2(aimed.y.abs())
+            .clamp(-config.max_angle, config.max_angle);
+        velocity.0 = Vec2::new(angle.sin(), angle.cos() * vertical) * speed;
+    }
+}
+
+fn track_last_touch_system(
+    mut commands: Commands,
+    mut collision_started: EventReader<CollisionStarted>,
+    balls: Query<(), With<Ball>>,
+    paddles: Query<&Player>,
+) {
+    for &CollisionStarted(a, b) in collision_started.read() {
+        for (ball, paddle) in [(a, b), (b, a)] {
+            let Ok(player) = paddles.get(paddle) else {
+                continue;
+            };
+
+            if balls.contains(ball) {
+                commands.entity(ball).insert(LastTouched(player.clone()));
+            };
+        }
+    }
 }
 
-fn hold_speed_system(balls: Query<&mut LinearVelocity, With<Ball>>) {
-    for mut velocity in balls {
-        velocity.0 = velocity.normalize() * MAX_SPEED;
+fn ramp_speed_on_collision_system(
+    ramp: Res<SpeedRamp>,
+    mut collision_started: EventReader<CollisionStarted>,
+    mut balls: Query<&mut BallSpeed, With<Ball>>,
+    blocks: Query<(), With<Block>>,
+    top_borders: Query<(), With<TopBorder>>,
+) {
+    for &CollisionStarted(a, b) in collision_started.read() {
+        for (ball, other) in [(a, b), (b, a)] {
+            let Ok(mut speed) = balls.get_mut(ball) else {
+                continue;
+            };
+
+            if blocks.contains(other) {
+                speed.increase(ramp.per_hit);
+            } else if top_borders.contains(other) {
+                speed.increase(ramp.top_border);
+            };
+        }
+    }
+}
+
+fn ramp_speed_over_time_system(
+    time: Res<Time>,
+    ramp: Res<SpeedRamp>,
+    balls: Query<&mut BallSpeed, (With<Ball>, Without<Held>)>,
+) {
+    for mut speed in balls {
+        speed.increase(ramp.per_second * time.delta_secs());
+    }
+}
+
+fn reset_stuck_timer_system(
+    mut collision_started: EventReader<CollisionStarted>,
+    mut balls: Query<&mut SinceContact, With<Ball>>,
+    touchable: Query<(), Or<(With<Block>, With<Player>)>>,
+) {
+    for &CollisionStarted(a, b) in collision_started.read() {
+        for (ball, other) in [(a, b), (b, a)] {
+            if !touchable.contains(other) {
+                continue;
+            };
+
+            if let Ok(mut since_contact) = balls.get_mut(ball) {
+                since_contact.0 = Duration::ZERO;
+            };
+        }
+    }
+}
+
+fn nudge_stuck_balls_system(
+    time: Res<Time>,
+    detector: Res<StuckDetector>,
+    mut rng: ResMut<GameRng>,
+    balls: Query<(&mut SinceContact, &mut LinearVelocity, Has<Held>), With<Ball>>,
+) {
+    for (mut since_contact, mut velocity, held) in balls {
+        if held {
+            since_contact.0 = Duration::ZERO;
+            continue;
+        };
+
+        since_contact.0 += time.delta();
+        if since_contact.0 < detector.timeout {
+            continue;
+        };
+
+        let side = if rng.random() { 1.0 } else { -1.0 };
+        let angle = side * rng.random_range(detector.nudge_angle / 2.0..=detector.nudge_angle);
+        velocity.0 = Vec2::from_angle(angle).rotate(velocity.0);
+        since_contact.0 = Duration::ZERO;
+
+        debug!("Nudged a stuck ball by {angle:.2} rad");
+    }
+}
+
+fn hold_speed_system(
+    speed_factor: Res<BallSpeedFactor>,
+    angle_guard: Res<AngleGuard>,
+    balls: Query<(&mut LinearVelocity, &BallSpeed), (With<Ball>, Without<Held>)>,
+) {
+    for (mut velocity, speed) in balls {
+        let direction = angle_guard.apply(velocity.normalize_or(Vec2::Y));
+        velocity.0 = direction * speed.current * speed_factor.0;
+    }
+}
+
+fn reset_speed_factor_system(mut speed_factor: ResMut<BallSpeedFactor>) {
+    *speed_factor = BallSpeedFactor::default();
+}
+
+fn follow_paddle_system(
+    player_size: Res<PlayerSize>,
+    game_mode: Res<GameMode>,
+    mut balls: Query<(&Held, &mut Transform, &mut LinearVelocity), With<Ball>>,
+    paddles: Query<(&Transform, &Player), Without<Ball>>,
+) {
+    for (held, mut transform, mut velocity) in balls.iter_mut() {
+        let Ok((paddle_transform, player)) = paddles.get(held.paddle) else {
+            continue;
+        };
+
+        // The ball rests on the side of the paddle that faces the blocks.
+        transform.translation.x = paddle_transform.translation.x + held.offset;
+        transform.translation.y = paddle_transform.translation.y
+            + (player_size.y / 2.0 + BALL_RADIUS) * game_mode.facing(player);
+        velocity.0 = Vec2::ZERO;
+    }
+}
+
+fn release_held_balls_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    inputs: Res<PlayerInputs>,
+    player_size: Res<PlayerSize>,
+    speed_factor: Res<BallSpeedFactor>,
+    game_mode: Res<GameMode>,
+    mut balls: Query<(Entity, &mut Held, &mut LinearVelocity, &BallSpeed), With<Ball>>,
+    paddles: Query<&Player>,
+) {
+    for (entity, mut held, mut velocity, speed) in balls.iter_mut() {
+        let player = paddles.get(held.paddle).ok();
+        let launched = player.is_some_and(|player| inputs.0.get(player).launch);
+        if !held.release.tick(time.delta()).finished() && !launched {
+            continue;
+        };
+
+        // Balls held near the paddle edge leave at a steeper angle.
+        let facing = player.map_or(1.0, |player| game_mode.facing(player));
+        let direction = Vec2::new(held.offset / player_size.x, facing).normalize();
+        velocity.0 = direction * speed.current * speed_factor.0;
+        commands.entity(entity).remove::<(Held, Serving)>();
     }
 }
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::player::Player;
+
+#[derive(Debug)]
+pub struct GameModePlugin;
+
+impl Plugin for GameModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMode>();
+    }
+}
+
+/// Chosen in the main menu before the game starts. Decides which paddles are spawned and where.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Resource, Serialize, Deserialize)]
+pub enum GameMode {
+    /// One paddle in the middle of the field.
+    #[default]
+    Solo,
+    /// Both players share the field with one paddle stacked above the other.
+    Coop,
+    /// Both players defend their own half of the field and compete for points. Each player has
+    /// their own lives.
+    Versus,
+    /// Like [`GameMode::Versus`], but [`Player::First`] defends the bottom edge and
+    /// [`Player::Second`] the top edge, with a mirrored block wall between them.
+    SplitField,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Solo,
+        GameMode::Coop,
+        GameMode::Versus,
+        GameMode::SplitField,
+    ];
+
+    /// The players that get a paddle in this mode.
+    pub fn players(&self) -> &'static [Player] {
+        match self {
+            GameMode::Solo => &[Player::First],
+            GameMode::Coop | GameMode::Versus | GameMode::SplitField => {
+                &[Player::First, Player::Second]
+            }
+        }
+    }
+
+    pub fn has_player(&self, player: &Player) -> bool {
+        self.players().contains(player)
+    }
+
+    /// Whether the players compete with their own score and lives.
+    pub fn is_competitive(&self) -> bool {
+        matches!(self, GameMode::Versus | GameMode::SplitField)
+    }
+
+    /// Whether balls leave the field through the top edge as well as the bottom edge.
+    pub fn is_split(&self) -> bool {
+        *self == GameMode::SplitField
+    }
+
+    /// The player who defends `position` and loses a life when a ball leaves the field there.
+    /// `None` while the players share their lives.
+    pub fn defender(&self, position: Vec2) -> Option<Player> {
+        match self {
+            GameMode::Solo | GameMode::Coop => None,
+            GameMode::Versus if position.x < 0.0 => Some(Player::First),
+            GameMode::SplitField if position.y < 0.0 => Some(Player::First),
+            GameMode::Versus | GameMode::SplitField => Some(Player::Second),
+        }
+    }
+
+    /// The horizontal range the paddle of `player` moves in, on a field `half_width` wide on either
+    /// side. In versus every player stays in the half they defend, see [`GameMode::defender`].
+    pub fn paddle_range(&self, player: &Player, half_width: f32) -> (f32, f32) {
+        match (self, player) {
+            (GameMode::Versus, Player::First) => (-half_width, 0.0),
+            (GameMode::Versus, Player::Second) => (0.0, half_width),
+            _ => (-half_width, half_width),
+        }
+    }
+
+    /// `1.0` if the paddle of `player` serves and shoots upwards, `-1.0` if downwards.
+    pub fn facing(&self, player: &Player) -> f32 {
+        match (self, player) {
+            (GameMode::SplitField, Player::Second) => -1.0,
+            _ => 1.0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Solo => "Solo",
+            GameMode::Coop => "Co-op",
+            GameMode::Versus => "Versus",
+            GameMode::SplitField => "Split Field",
+        }
+    }
+}
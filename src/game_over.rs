@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+
+use crate::{RestartLevel, StopGame, app_state::AppState, score::Score, ui};
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::GameOver), game_over_setup)
+            .add_systems(
+                OnExit(AppState::GameOver),
+                ui::despawn_screen::<GameOverScreen>,
+            )
+            .add_systems(
+                Update,
+                (retry_button_pressed_system, menu_button_pressed_system)
+                    .run_if(in_state(AppState::GameOver)),
+            );
+    }
+}
+
+#[derive(Debug, Component)]
+struct GameOverScreen;
+
+#[derive(Debug, Component)]
+struct RetryButton;
+
+#[derive(Debug, Component)]
+struct MenuButton;
+
+fn game_over_setup(mut commands: Commands, score: Res<Score>) {
+    commands.spawn((
+        GameOverScreen,
+        ui::screen(),
+        children![
+            ui::title("GAME OVER"),
+            ui::label(format!("Score: {}", score.points)),
+            (
+                ui::button_column(),
+                children![
+                    (ui::button("Retry"), RetryButton),
+                    (ui::button("Main Menu"), MenuButton),
+                ]
+            )
+        ],
+    ));
+}
+
+fn retry_button_pressed_system(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<RetryButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(RestartLevel);
+        };
+    }
+}
+
+fn menu_button_pressed_system(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<MenuButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(StopGame);
+        };
+    }
+}
@@ -0,0 +1,205 @@
+//! Gamepads control the paddles and navigate the menus. Newly connected gamepads are assigned to
+//! the first player that does not have one yet.
+
+use bevy::{prelude::*, ui::UiSystem};
+
+use crate::player::Player;
+
+const DEFAULT_DEAD_ZONE: f32 = 0.2;
+/// Releases the balls resting on the paddle. Shown as "A" in the HUD.
+pub const LAUNCH_BUTTON: GamepadButton = GamepadButton::South;
+
+#[derive(Debug)]
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GamepadConfig>();
+
+        app.add_observer(assign_gamepad_observer)
+            .add_observer(unassign_gamepad_observer);
+
+        // Runs right after the UI updated `Interaction` from the mouse, so the button systems in
+        // `Update` see presses from the gamepad just like clicks.
+        app.add_systems(PreUpdate, menu_navigation_system.after(UiSystem::Focus));
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct GamepadConfig {
+    /// Stick deflections below this are ignored.
+    pub dead_zone: f32,
+}
+
+/// The player a gamepad controls.
+#[derive(Debug, Component)]
+pub struct PlayerGamepad(pub Player);
+
+/// The button that gamepad menu navigation currently points at. Buttons are styled from it instead
+/// of `Interaction`, which the UI recomputes from the mouse every frame.
+#[derive(Debug, Component)]
+pub struct GamepadFocus;
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            dead_zone: DEFAULT_DEAD_ZONE,
+        }
+    }
+}
+
+impl GamepadConfig {
+    /// Horizontal movement from the left stick or the D-pad, between -1 and 1.
+    pub fn horizontal_axis(&self, gamepad: &Gamepad) -> f32 {
+        let dpad = f32::from(u8::from(gamepad.pressed(GamepadButton::DPadRight)))
+            - f32::from(u8::from(gamepad.pressed(GamepadButton::DPadLeft)));
+        if dpad != 0.0 {
+            return dpad;
+        };
+
+        let stick = gamepad.left_stick().x;
+        if stick.abs() < self.dead_zone {
+            return 0.0;
+        };
+
+        // Start at zero right outside the dead zone instead of jumping to it.
+        stick.signum() * (stick.abs() - self.dead_zone) / (1.0 - self.dead_zone)
+    }
+}
+
+/// Whether `player` pressed `button` on their gamepad this frame.
+pub fn just_pressed(
+    gamepads: &Query<(&Gamepad, &PlayerGamepad)>,
+    player: &Player,
+    button: GamepadButton,
+) -> bool {
+    gamepads
+        .iter()
+        .any(|(gamepad, assigned)| assigned.0 == *player && gamepad.just_pressed(button))
+}
+
+fn assign_gamepad_observer(
+    trigger: Trigger<OnAdd, Gamepad>,
+    mut commands: Commands,
+    assigned: Query<&PlayerGamepad>,
+) {
+    let free = [Player::First, Player::Second]
+        .into_iter()
+        .find(|player| !assigned.iter().any(|assigned| assigned.0 == *player));
+
+    match free {
+        Some(player) => {
+            info!("Gamepad {} controls {:?}", trigger.target(), player);
+            commands
+                .entity(trigger.target())
+                .insert(PlayerGamepad(player));
+        }
+        None => info!(
+            "Gamepad {} connected, but every player has one",
+            trigger.target()
+        ),
+    };
+}
+
+fn unassign_gamepad_observer(
+    trigger: Trigger<OnRemove, Gamepad>,
+    mut commands: Commands,
+    assigned: Query<&PlayerGamepad>,
+) {
+    let Ok(assigned) = assigned.get(trigger.target()) else {
+        return;
+    };
+
+    info!("Gamepad of {:?} disconnected", assigned.0);
+    commands
+        .entity(trigger.target())
+        .try_remove::<PlayerGamepad>();
+}
+
+fn menu_navigation_system(
+    mut commands: Commands,
+    config: Res<GamepadConfig>,
+    gamepads: Query<&Gamepad>,
+    mut buttons: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &InheritedVisibility,
+            &mut Interaction,
+            Has<GamepadFocus>,
+        ),
+        With<Button>,
+    >,
+    mut stick_was_pushed: Local<bool>,
+) {
+    // A press only lasts for one frame. The UI only resets it once the mouse is released.
+    for (_, _, _, mut interaction, focused) in buttons.iter_mut() {
+        if focused && *interaction == Interaction::Pressed {
+            *interaction = Interaction::None;
+        };
+    }
+
+    let stick = gamepads
+        .iter()
+        .map(|gamepad| gamepad.left_stick().y)
+        .find(|y| y.abs() >= config.dead_zone.max(0.5))
+        .unwrap_or_default();
+    let stick_pushed = stick != 0.0;
+    let stick_step = if stick_pushed && !*stick_was_pushed {
+        // The stick points up for positive values, but the menus grow downwards.
+        -stick.signum() as i32
+    } else {
+        0
+    };
+    *stick_was_pushed = stick_pushed;
+
+    let step = gamepads.iter().fold(stick_step, |step, gamepad| {
+        step + i32::from(gamepad.just_pressed(GamepadButton::DPadDown))
+            - i32::from(gamepad.just_pressed(GamepadButton::DPadUp))
+    });
+    let pressed = gamepads
+        .iter()
+        .any(|gamepad| gamepad.just_pressed(GamepadButton::South));
+    if step == 0 && !pressed {
+        return;
+    };
+
+    // Order the visible buttons the way they are laid out, top to bottom and left to right.
+    let mut visible = buttons
+        .iter()
+        .filter(|(_, _, visibility, _, _)| visibility.get())
+        .map(|(entity, transform, _, _, focused)| {
+            (entity, transform.translation().truncate(), focused)
+        })
+        .collect::<Vec<_>>();
+    if visible.is_empty() {
+        return;
+    };
+    visible.sort_by(|(_, a, _), (_, b, _)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let current = visible.iter().position(|(_, _, focused)| *focused);
+    let next = match current {
+        Some(current) => (current as i32 + step).rem_euclid(visible.len() as i32) as usize,
+        // The first input only shows where the focus starts.
+        None => 0,
+    };
+    let next_entity = visible[next].0;
+
+    if current != Some(next) {
+        for (entity, _, _, _, focused) in buttons.iter() {
+            if focused {
+                commands.entity(entity).remove::<GamepadFocus>();
+            };
+        }
+        commands.entity(next_entity).insert(GamepadFocus);
+    };
+
+    // The first input only shows where the focus starts, it does not press the button.
+    if !pressed || current.is_none() {
+        return;
+    };
+    // The button systems in `Update` see the press just like a click.
+    if let Ok((_, _, _, mut interaction, _)) = buttons.get_mut(next_entity) {
+        *interaction = Interaction::Pressed;
+    };
+}
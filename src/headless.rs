@@ -0,0 +1,70 @@
+//! Runs the game without a window, a GPU or audio, e.g. to play back replays on a server or to
+//! step the simulation from a test. The playfield has a fixed size instead of following a window.
+
+use std::time::Duration;
+
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    asset::AssetPlugin,
+    audio::{AudioLoader, GlobalVolume},
+    input::InputPlugin,
+    log::LogPlugin,
+    prelude::*,
+    scene::ScenePlugin,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+use bevy_embedded_assets::{EmbeddedAssetPlugin, PluginMode};
+
+use crate::{simulation::TICK_RATE, world::Playfield};
+
+/// Replaces `DefaultPlugins`. The clock advances by exactly one simulation step per
+/// [`App::update`], so the game runs as fast as it can and the same number of updates always
+/// plays out the same.
+#[derive(Debug, Default)]
+pub struct HeadlessPlugin {
+    pub playfield: Playfield,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            EmbeddedAssetPlugin {
+                mode: PluginMode::ReplaceDefault,
+            },
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+            LogPlugin::default(),
+            AssetPlugin::default(),
+            ScenePlugin,
+            TransformPlugin,
+            InputPlugin,
+            StatesPlugin,
+        ));
+
+        let step = Time::<Fixed>::from_hz(TICK_RATE).timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(step))
+            .insert_resource(self.playfield)
+            .insert_resource(Headless);
+
+        // The game spawns meshes and sounds, they are kept as assets without being drawn or played.
+        app.init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_asset::<AudioSource>()
+            .init_asset_loader::<AudioLoader>()
+            .init_resource::<GlobalVolume>();
+
+        app.add_systems(Last, despawn_sounds_system);
+    }
+}
+
+/// Exists while the game runs without a window. Nobody watches the game, so whatever would wait
+/// for the player ends the app instead.
+#[derive(Debug, Resource)]
+pub struct Headless;
+
+/// Sounds despawn themselves once they played, which never happens without audio.
+fn despawn_sounds_system(mut commands: Commands, sounds: Query<Entity, With<AudioPlayer>>) {
+    for sound in sounds {
+        commands.entity(sound).despawn();
+    }
+}
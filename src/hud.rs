@@ -0,0 +1,351 @@
+use bevy::prelude::*;
+
+use crate::{
+    app_state::AppState,
+    ball::{Held, Serving},
+    game_mode::GameMode,
+    gamepad::PlayerGamepad,
+    input::{Action, InputBindings, key_name},
+    lives::{LifeGained, LifeLost, Lives},
+    player::Player,
+    power_up::{ActivePowerUps, PowerUps},
+    score::{Combo, ComboChanged, Score, ScoreChanged, StartLevelScore},
+    ui,
+    world::level::{CurrentLevel, Level},
+};
+
+/// Shows score, lives, the level name and per player info while in game.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(update_score_text_observer)
+            .add_observer(update_combo_text_observer)
+            .add_observer(update_lives_text_observer)
+            .add_observer(update_lives_text_on_gain_observer);
+
+        app.add_systems(OnEnter(AppState::InGame), hud_setup.after(StartLevelScore))
+            .add_systems(OnExit(AppState::InGame), ui::despawn_screen::<Hud>)
+            .add_systems(
+                Update,
+                (
+                    update_power_up_text_system,
+                    update_serve_prompt_system.run_if(serve_prompt_changed),
+                    update_player_info_text_system.run_if(resource_changed::<InputBindings>),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct Hud;
+
+#[derive(Debug, Component)]
+struct ScoreText;
+
+#[derive(Debug, Component)]
+struct ComboText;
+
+#[derive(Debug, Component)]
+struct LivesText;
+
+#[derive(Debug, Component)]
+struct ServePrompt;
+
+/// Shows the keys of a player.
+#[derive(Debug, Component)]
+struct PlayerInfoText(Player);
+
+/// A player's own score in versus.
+#[derive(Debug, Component)]
+struct PlayerScoreText(Player);
+
+/// A player's own lives in versus.
+#[derive(Debug, Component)]
+struct PlayerLivesText(Player);
+
+/// Lists the power-ups active on a player's paddle.
+#[derive(Debug, Component)]
+struct PowerUpText(Player);
+
+fn hud_setup(
+    mut commands: Commands,
+    score: Res<Score>,
+    combo: Res<Combo>,
+    lives: Res<Lives>,
+    bindings: Res<InputBindings>,
+    game_mode: Res<GameMode>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+) {
+    let level_name = levels
+        .get(&current_level.0)
+        .map(|level| level.name.clone())
+        .unwrap_or_default();
+    let versus = game_mode.is_competitive();
+
+    commands.spawn((
+        Hud,
+        ui::screen(),
+        children![(ui::label(String::new()), ServePrompt)],
+    ));
+
+    commands.spawn((
+        Hud,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::End,
+            padding: UiRect::all(Val::Px(8.0)),
+            ..Default::default()
+        },
+        children![
+            player_info(Player::First, &bindings, &game_mode, &score, &lives),
+            (
+                Node {
+                    column_gap: Val::Px(24.0),
+                    ..Default::default()
+                },
+                children![
+                    ui::label(level_name),
+                    (
+                        ui::label(score_text(score.points)),
+                        ScoreText,
+                        shown_if(!versus)
+                    ),
+                    (ui::label(combo_text(combo.multiplier())), ComboText),
+                    (
+                        ui::label(lives_text(lives.remaining)),
+                        LivesText,
+                        shown_if(!versus)
+                    ),
+                ]
+            ),
+            player_info(Player::Second, &bindings, &game_mode, &score, &lives),
+        ],
+    ));
+}
+
+fn player_info(
+    player: Player,
+    bindings: &InputBindings,
+    game_mode: &GameMode,
+    score: &Score,
+    lives: &Lives,
+) -> impl Bundle {
+    // Hidden instead of left out, so the score in the middle stays centered.
+    let visibility = if game_mode.has_player(&player) {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    let versus = game_mode.is_competitive();
+
+    (
+        Node {
+            flex_direction: FlexDirection::Column,
+            ..Default::default()
+        },
+        visibility,
+        children![
+            (ui::label(String::new()), PowerUpText(player.clone())),
+            (
+                ui::label(score_text(*score.per_player.get(&player))),
+                PlayerScoreText(player.clone()),
+                shown_if(versus)
+            ),
+            (
+                ui::label(lives_text(*lives.per_player.get(&player))),
+                PlayerLivesText(player.clone()),
+                shown_if(versus)
+            ),
+            (
+                ui::label(player_info_text(&player, bindings)),
+                PlayerInfoText(player)
+            ),
+        ],
+    )
+}
+
+/// Takes a text out of the layout unless `shown`.
+fn shown_if(shown: bool) -> Node {
+    Node {
+        display: if shown { Display::Flex } else { Display::None },
+        ..Default::default()
+    }
+}
+
+fn update_score_text_observer(
+    trigger: Trigger<ScoreChanged>,
+    score: Res<Score>,
+    mut texts: Query<&mut Text, (With<ScoreText>, Without<PlayerScoreText>)>,
+    mut player_texts: Query<(&mut Text, &PlayerScoreText)>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = score_text(trigger.points);
+    }
+
+    for (mut text, player_score_text) in player_texts.iter_mut() {
+        text.0 = score_text(*score.per_player.get(&player_score_text.0));
+    }
+}
+
+fn update_combo_text_observer(
+    trigger: Trigger<ComboChanged>,
+    mut texts: Query<&mut Text, With<ComboText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = combo_text(trigger.multiplier);
+    }
+}
+
+fn update_lives_text_observer(
+    trigger: Trigger<LifeLost>,
+    mut texts: Query<&mut Text, (With<LivesText>, Without<PlayerLivesText>)>,
+    mut player_texts: Query<(&mut Text, &PlayerLivesText)>,
+) {
+    set_lives_text(
+        trigger.remaining,
+        trigger.player.as_ref(),
+        &mut texts,
+        &mut player_texts,
+    );
+}
+
+fn update_lives_text_on_gain_observer(
+    trigger: Trigger<LifeGained>,
+    mut texts: Query<&mut Text, (With<LivesText>, Without<PlayerLivesText>)>,
+    mut player_texts: Query<(&mut Text, &PlayerLivesText)>,
+) {
+    set_lives_text(
+        trigger.remaining,
+        trigger.player.as_ref(),
+        &mut texts,
+        &mut player_texts,
+    );
+}
+
+/// Updates the shared lives, or the lives of `player` if they have their own.
+fn set_lives_text(
+    remaining: u32,
+    player: Option<&Player>,
+    texts: &mut Query<&mut Text, (With<LivesText>, Without<PlayerLivesText>)>,
+    player_texts: &mut Query<(&mut Text, &PlayerLivesText)>,
+) {
+    let Some(player) = player else {
+        for mut text in texts.iter_mut() {
+            text.0 = lives_text(remaining);
+        }
+        return;
+    };
+
+    for (mut text, player_lives_text) in player_texts.iter_mut() {
+        if player_lives_text.0 == *player {
+            text.0 = lives_text(remaining);
+        };
+    }
+}
+
+fn update_power_up_text_system(
+    power_ups: Res<PowerUps>,
+    paddles: Query<(&Player, &ActivePowerUps), Changed<ActivePowerUps>>,
+    mut texts: Query<(&mut Text, &PowerUpText)>,
+) {
+    for (player, active) in paddles.iter() {
+        let names = active
+            .0
+            .iter()
+            .filter_map(|active| power_ups.get(active.id))
+            .map(|power_up| power_up.name())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        for (mut text, power_up_text) in texts.iter_mut() {
+            if power_up_text.0 == *player {
+                text.0 = names.clone();
+            };
+        }
+    }
+}
+
+fn update_player_info_text_system(
+    bindings: Res<InputBindings>,
+    mut texts: Query<(&mut Text, &PlayerInfoText)>,
+) {
+    for (mut text, player_info_text) in texts.iter_mut() {
+        text.0 = self::player_info_text(&player_info_text.0, &bindings);
+    }
+}
+
+/// The prompt changes when a ball is served or launched, or the keys or gamepads change.
+fn serve_prompt_changed(
+    bindings: Res<InputBindings>,
+    served: Query<(), Added<Serving>>,
+    mut launched: RemovedComponents<Serving>,
+    gamepads: Query<(), Changed<PlayerGamepad>>,
+    mut unassigned: RemovedComponents<PlayerGamepad>,
+) -> bool {
+    // Reads every removal, so they are not seen again next frame.
+    let launched = launched.read().count() > 0;
+    let unassigned = unassigned.read().count() > 0;
+    bindings.is_changed() || !served.is_empty() || !gamepads.is_empty() || launched || unassigned
+}
+
+fn update_serve_prompt_system(
+    bindings: Res<InputBindings>,
+    balls: Query<&Held, With<Serving>>,
+    paddles: Query<&Player>,
+    gamepads: Query<&PlayerGamepad>,
+    mut prompts: Query<&mut Text, With<ServePrompt>>,
+) {
+    let prompt = balls
+        .iter()
+        .find_map(|held| paddles.get(held.paddle).ok())
+        .map(|player| {
+            let has_gamepad = gamepads.iter().any(|assigned| assigned.0 == *player);
+            serve_prompt_text(player, &bindings, has_gamepad)
+        })
+        .unwrap_or_default();
+
+    for mut text in prompts.iter_mut() {
+        if text.0 != prompt {
+            text.0 = prompt.clone();
+        };
+    }
+}
+
+fn score_text(points: u32) -> String {
+    format!("Score: {points}")
+}
+
+fn combo_text(multiplier: u32) -> String {
+    format!("Combo: x{multiplier}")
+}
+
+fn lives_text(remaining: u32) -> String {
+    format!("Lives: {remaining}")
+}
+
+/// Names the gamepad button only if the player has a gamepad.
+fn serve_prompt_text(player: &Player, bindings: &InputBindings, has_gamepad: bool) -> String {
+    let key = key_name(bindings.key(player, Action::Launch));
+    let controls = if has_gamepad {
+        format!("{key} or A")
+    } else {
+        key
+    };
+
+    format!("{}: press {controls} to launch", player.short_name())
+}
+
+fn player_info_text(player: &Player, bindings: &InputBindings) -> String {
+    format!(
+        "{}  {} / {}",
+        player.short_name(),
+        key_name(bindings.key(player, Action::MoveLeft)),
+        key_name(bindings.key(player, Action::MoveRight))
+    )
+}
@@ -0,0 +1,358 @@
+//! Maps keys to per player actions. The bindings can be changed in the pause menu and are saved to
+//! `bindings.ron` in the user's config directory.
+//!
+//! Gameplay never reads the devices itself. Every frame the [`ReadInput`] systems collect what the
+//! players did into [`PlayerInputs`], which the next simulation step consumes.
+
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::PathBuf,
+};
+
+use bevy::{app::RunFixedMainLoopSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    app_state::GameState,
+    gamepad::{self, GamepadConfig, PlayerGamepad},
+    player::{PerPlayer, Player},
+};
+
+const CONFIG_DIR_NAME: &str = "breakout";
+const BINDINGS_FILE_NAME: &str = "bindings.ron";
+
+#[derive(Debug)]
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_bindings())
+            .init_resource::<PlayerInputs>();
+
+        app.configure_sets(
+            RunFixedMainLoop,
+            ReadInput
+                .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop)
+                .run_if(in_state(GameState::Running)),
+        );
+
+        app.add_systems(OnExit(GameState::Running), reset_inputs_system)
+            .add_systems(RunFixedMainLoop, read_devices_system.in_set(ReadInput))
+            .add_systems(FixedLast, consume_inputs_system);
+    }
+}
+
+/// Systems that write the input of the coming simulation steps to [`PlayerInputs`]. They run once
+/// per frame, before the fixed timestep catches up.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct ReadInput;
+
+/// What one player does with their paddle during a simulation step.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PaddleInput {
+    /// Horizontal movement between -1 and 1.
+    pub axis: f32,
+    /// Moves the paddle straight to this x position, e.g. to follow the mouse.
+    pub target: Option<f32>,
+    /// Releases the balls resting on the paddle.
+    pub launch: bool,
+}
+
+/// The input of both players for the next simulation step. `launch` and `target` only last for
+/// one step, so a press is never applied twice when a frame runs several steps.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Resource)]
+pub struct PlayerInputs(pub PerPlayer<PaddleInput>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Launch,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 4] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Launch,
+        Action::Pause,
+    ];
+}
+
+/// The keys that trigger the actions of both players.
+#[derive(Debug, Clone, PartialEq, Eq, Resource, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub first: PlayerBindings,
+    pub second: PlayerBindings,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerBindings {
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    pub launch: KeyCode,
+    pub pause: KeyCode,
+}
+
+#[derive(Debug, Error)]
+pub enum BindingsError {
+    #[error("Could not find a config directory")]
+    NoConfigDir,
+    #[error("Could not access the bindings file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Bindings file is malformed: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Could not serialize the bindings: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            first: PlayerBindings {
+                move_left: KeyCode::KeyA,
+                move_right: KeyCode::KeyD,
+                launch: KeyCode::KeyW,
+                pause: KeyCode::Escape,
+            },
+            second: PlayerBindings {
+                move_left: KeyCode::ArrowLeft,
+                move_right: KeyCode::ArrowRight,
+                launch: KeyCode::ArrowUp,
+                pause: KeyCode::KeyP,
+            },
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn key(&self, player: &Player, action: Action) -> KeyCode {
+        let bindings = match player {
+            Player::First => &self.first,
+            Player::Second => &self.second,
+        };
+
+        match action {
+            Action::MoveLeft => bindings.move_left,
+            Action::MoveRight => bindings.move_right,
+            Action::Launch => bindings.launch,
+            Action::Pause => bindings.pause,
+        }
+    }
+
+    /// Binds `key` to `action`. If another action already used `key`, the two actions swap keys
+    /// and the other action is returned.
+    pub fn rebind(
+        &mut self,
+        player: &Player,
+        action: Action,
+        key: KeyCode,
+    ) -> Option<(Player, Action)> {
+        let conflict = self.find(key).filter(|(other_player, other_action)| {
+            (other_player, *other_action) != (player, action)
+        });
+        let previous = self.key(player, action);
+
+        if let Some((other_player, other_action)) = &conflict {
+            *self.key_mut(other_player, *other_action) = previous;
+        };
+        *self.key_mut(player, action) = key;
+
+        conflict
+    }
+
+    /// The action that `key` is bound to.
+    pub fn find(&self, key: KeyCode) -> Option<(Player, Action)> {
+        [Player::First, Player::Second]
+            .into_iter()
+            .flat_map(|player| Action::ALL.map(|action| (player.clone(), action)))
+            .find(|(player, action)| self.key(player, *action) == key)
+    }
+
+    /// Keys that are bound to more than one action. Only possible in hand edited files.
+    fn conflicts(&self) -> Vec<KeyCode> {
+        let keys = [&self.first, &self.second]
+            .into_iter()
+            .flat_map(|bindings| {
+                [
+                    bindings.move_left,
+                    bindings.move_right,
+                    bindings.launch,
+                    bindings.pause,
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let mut conflicts = Vec::new();
+        for (index, key) in keys.iter().enumerate() {
+            if keys[index + 1..].contains(key) && !conflicts.contains(key) {
+                conflicts.push(*key);
+            };
+        }
+
+        conflicts
+    }
+
+    pub fn pressed(&self, keys: &ButtonInput<KeyCode>, player: &Player, action: Action) -> bool {
+        keys.pressed(self.key(player, action))
+    }
+
+    pub fn just_pressed(
+        &self,
+        keys: &ButtonInput<KeyCode>,
+        player: &Player,
+        action: Action,
+    ) -> bool {
+        keys.just_pressed(self.key(player, action))
+    }
+
+    fn key_mut(&mut self, player: &Player, action: Action) -> &mut KeyCode {
+        let bindings = match player {
+            Player::First => &mut self.first,
+            Player::Second => &mut self.second,
+        };
+
+        match action {
+            Action::MoveLeft => &mut bindings.move_left,
+            Action::MoveRight => &mut bindings.move_right,
+            Action::Launch => &mut bindings.launch,
+            Action::Pause => &mut bindings.pause,
+        }
+    }
+
+    /// Writes the bindings to the config directory.
+    pub fn save(&self) -> Result<(), BindingsError> {
+        let path = bindings_path().ok_or(BindingsError::NoConfigDir)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        };
+
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(&path, contents)?;
+
+        info!("Saved input bindings to {}", path.display());
+        Ok(())
+    }
+
+    fn load() -> Result<Option<Self>, BindingsError> {
+        let path = bindings_path().ok_or(BindingsError::NoConfigDir)?;
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        info!("Loaded input bindings from {}", path.display());
+        Ok(Some(ron::from_str(&contents)?))
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Launch => "Launch",
+            Action::Pause => "Pause",
+        };
+
+        f.write_str(name)
+    }
+}
+
+pub fn read_devices_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<InputBindings>,
+    gamepad_config: Res<GamepadConfig>,
+    gamepads: Query<(&Gamepad, &PlayerGamepad)>,
+    mut inputs: ResMut<PlayerInputs>,
+) {
+    for player in [Player::First, Player::Second] {
+        let keyboard_axis = f32::from(
+            i8::from(bindings.pressed(&keys, &player, Action::MoveRight))
+                - i8::from(bindings.pressed(&keys, &player, Action::MoveLeft)),
+        );
+        let gamepad_axis = gamepads
+            .iter()
+            .filter(|(_, assigned)| assigned.0 == player)
+            .map(|(gamepad, _)| gamepad_config.horizontal_axis(gamepad))
+            .sum::<f32>();
+        let launch = bindings.just_pressed(&keys, &player, Action::Launch)
+            || gamepad::just_pressed(&gamepads, &player, gamepad::LAUNCH_BUTTON);
+
+        let input = inputs.0.get_mut(&player);
+        input.axis = (keyboard_axis + gamepad_axis).clamp(-1.0, 1.0);
+        // A press stays until a step used it, even if this frame runs no step.
+        input.launch |= launch;
+    }
+}
+
+fn consume_inputs_system(mut inputs: ResMut<PlayerInputs>) {
+    for player in [Player::First, Player::Second] {
+        let input = inputs.0.get_mut(&player);
+        input.target = None;
+        input.launch = false;
+    }
+}
+
+/// Presses made in the pause menu do not carry over into the game.
+fn reset_inputs_system(mut inputs: ResMut<PlayerInputs>) {
+    *inputs = PlayerInputs::default();
+}
+
+/// A short name for `key` to show in the UI, e.g. `W` instead of `KeyW`.
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+
+    ["Key", "Digit", "Arrow"]
+        .into_iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .filter(|stripped| !stripped.is_empty())
+        .map(str::to_string)
+        .unwrap_or(name)
+}
+
+fn load_bindings() -> InputBindings {
+    let bindings = match InputBindings::load() {
+        Ok(bindings) => bindings.unwrap_or_default(),
+        Err(error) => {
+            warn!("Using the default input bindings. {error}");
+            InputBindings::default()
+        }
+    };
+
+    for key in bindings.conflicts() {
+        warn!("{key:?} is bound to more than one action");
+    }
+
+    bindings
+}
+
+fn bindings_path() -> Option<PathBuf> {
+    Some(config_dir()?.join(CONFIG_DIR_NAME).join(BINDINGS_FILE_NAME))
+}
+
+#[cfg(target_os = "windows")]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| {
+        PathBuf::from(home)
+            .join("Library")
+            .join("Application Support")
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    StopGame,
+    app_state::AppState,
+    score::Score,
+    ui,
+    world::{
+        campaign::{Campaign, LevelStats, NextLevel},
+        level::{CurrentLevel, Level},
+    },
+};
+
+/// How long the summary stays on screen before the next level loads on its own.
+const NEXT_LEVEL_DELAY: Duration = Duration::from_secs(5);
+
+/// The screens shown after a level or the whole campaign was cleared.
+pub struct LevelCompletePlugin;
+
+impl Plugin for LevelCompletePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::LevelComplete), level_complete_setup)
+            .add_systems(
+                OnExit(AppState::LevelComplete),
+                (
+                    ui::despawn_screen::<LevelCompleteScreen>,
+                    |mut commands: Commands| commands.remove_resource::<NextLevelTimer>(),
+                ),
+            )
+            .add_systems(OnEnter(AppState::CampaignWon), campaign_won_setup)
+            .add_systems(
+                OnExit(AppState::CampaignWon),
+                ui::despawn_screen::<CampaignWonScreen>,
+            )
+            .add_systems(
+                Update,
+                (next_level_system, update_countdown_text_system)
+                    .chain()
+                    .run_if(in_state(AppState::LevelComplete)),
+            )
+            .add_systems(
+                Update,
+                menu_button_pressed_system
+                    .run_if(in_state(AppState::LevelComplete).or(in_state(AppState::CampaignWon))),
+            );
+    }
+}
+
+#[derive(Debug, Component)]
+struct LevelCompleteScreen;
+
+#[derive(Debug, Component)]
+struct CampaignWonScreen;
+
+#[derive(Debug, Component)]
+struct NextLevelButton;
+
+#[derive(Debug, Component)]
+struct CountdownText;
+
+/// Counts down to loading the next level. The button skips the wait.
+#[derive(Debug, Resource)]
+struct NextLevelTimer(Timer);
+
+#[derive(Debug, Component)]
+struct MenuButton;
+
+fn level_complete_setup(
+    mut commands: Commands,
+    campaign: Res<Campaign>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    level_stats: Res<LevelStats>,
+    score: Res<Score>,
+) {
+    let level_name = levels
+        .get(&current_level.0)
+        .map(|level| level.name.as_str())
+        .unwrap_or_default();
+
+    commands.insert_resource(NextLevelTimer(Timer::new(
+        NEXT_LEVEL_DELAY,
+        TimerMode::Once,
+    )));
+
+    commands.spawn((
+        LevelCompleteScreen,
+        ui::screen(),
+        children![
+            ui::title("LEVEL COMPLETE"),
+            ui::label(format!(
+                "Level {}/{}: {level_name}",
+                campaign.current() + 1,
+                campaign.level_count()
+            )),
+            ui::label(format!(
+                "{} blocks broken in {:.1}s",
+                level_stats.blocks_broken,
+                level_stats.elapsed.as_secs_f32()
+            )),
+            ui::label(format!("Score: {}", score.points)),
+            (ui::label(String::new()), CountdownText),
+            (
+                ui::button_column(),
+                children![
+                    (ui::button("Next Level"), NextLevelButton),
+                    (ui::button("Main Menu"), MenuButton),
+                ]
+            )
+        ],
+    ));
+}
+
+fn campaign_won_setup(mut commands: Commands, campaign: Res<Campaign>, score: Res<Score>) {
+    commands.spawn((
+        CampaignWonScreen,
+        ui::screen(),
+        children![
+            ui::title("CAMPAIGN WON"),
+            ui::label(format!("All {} levels cleared", campaign.level_count())),
+            ui::label(format!("Final score: {}", score.points)),
+            (
+                ui::button_column(),
+                children![(ui::button("Main Menu"), MenuButton)]
+            )
+        ],
+    ));
+}
+
+fn next_level_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    timer: Option<ResMut<NextLevelTimer>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<NextLevelButton>)>,
+) {
+    let Some(mut timer) = timer else {
+        return;
+    };
+
+    let pressed = buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if pressed || timer.0.tick(time.delta()).finished() {
+        // Removing the timer makes sure the level only advances once.
+        commands.remove_resource::<NextLevelTimer>();
+        commands.trigger(NextLevel);
+    };
+}
+
+fn update_countdown_text_system(
+    timer: Option<Res<NextLevelTimer>>,
+    mut texts: Query<&mut Text, With<CountdownText>>,
+) {
+    let Some(timer) = timer else {
+        return;
+    };
+
+    let text = format!(
+        "Next level in {}s",
+        timer.0.remaining().as_secs_f32().ceil()
+    );
+    for mut countdown_text in texts.iter_mut() {
+        countdown_text.set_if_neq(Text(text.clone()));
+    }
+}
+
+fn menu_button_pressed_system(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<MenuButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(StopGame);
+        };
+    }
+}
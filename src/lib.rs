@@ -0,0 +1,87 @@
+//! This is a Breakout clone. [`BreakoutPlugins`] adds the whole game to an app that already has
+//! `DefaultPlugins` or the [`HeadlessPlugin`](headless::HeadlessPlugin).
+
+use bevy::{app::PluginGroupBuilder, prelude::*};
+
+use crate::{
+    app_state::AppStatePlugin, ball::BallPlugin, game_mode::GameModePlugin,
+    game_over::GameOverPlugin, gamepad::GamepadPlugin, hud::HudPlugin, input::InputPlugin,
+    level_complete::LevelCompletePlugin, lives::LivesPlugin, main_menu::MainMenuPlugin,
+    net::NetPlugin, pause_menu::PauseMenuPlugin, player::PlayerPlugin, power_up::PowerUpPlugin,
+    replay::ReplayPlugin, score::ScorePlugin, simulation::SimulationPlugin, ui::UiPlugin,
+    versus::VersusPlugin, world::WorldPlugin,
+};
+
+pub mod app_state;
+pub mod args;
+pub mod ball;
+pub mod game_mode;
+pub mod game_over;
+pub mod gamepad;
+pub mod headless;
+pub mod hud;
+pub mod input;
+pub mod level_complete;
+pub mod lives;
+pub mod main_menu;
+pub mod net;
+pub mod pause_menu;
+pub mod player;
+pub mod power_up;
+pub mod replay;
+pub mod score;
+pub mod simulation;
+pub mod ui;
+pub mod versus;
+pub mod world;
+
+/// Every plugin of the game, including the physics. Networking and replays are off by default,
+/// set [`NetPlugin`] or [`ReplayPlugin`] to turn them on.
+///
+/// These plugins can be disabled, e.g. in tests: [`UiPlugin`], [`MainMenuPlugin`],
+/// [`LevelCompletePlugin`], [`GameOverPlugin`], [`VersusPlugin`], [`HudPlugin`],
+/// [`PauseMenuPlugin`], [`NetPlugin`] and [`ReplayPlugin`]. Without the menus the game is started
+/// by triggering [`StartGame`], and without [`VersusPlugin`] versus rounds never end. Every other
+/// plugin is part of the simulation and required, including [`GamepadPlugin`] because the input
+/// reads its configuration.
+#[derive(Debug)]
+pub struct BreakoutPlugins;
+
+impl PluginGroup for BreakoutPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add_group(avian2d::PhysicsPlugins::default())
+            .add(AppStatePlugin)
+            .add(UiPlugin)
+            .add(MainMenuPlugin)
+            .add(LevelCompletePlugin)
+            .add(GameOverPlugin)
+            .add(VersusPlugin)
+            .add(HudPlugin)
+            .add(PauseMenuPlugin)
+            .add(WorldPlugin)
+            .add(PlayerPlugin)
+            .add(BallPlugin)
+            .add(LivesPlugin)
+            .add(ScorePlugin)
+            .add(PowerUpPlugin)
+            .add(GamepadPlugin)
+            .add(InputPlugin)
+            .add(GameModePlugin)
+            .add(SimulationPlugin)
+            .add(NetPlugin::default())
+            .add(ReplayPlugin::default())
+    }
+}
+
+/// Starts the campaign, or a round in versus modes, from the first level.
+#[derive(Debug, Event)]
+pub struct StartGame;
+
+/// Returns to the main menu.
+#[derive(Debug, Event)]
+pub struct StopGame;
+
+/// Reloads the current level.
+#[derive(Debug, Event)]
+pub struct RestartLevel;
@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    RestartLevel, StartGame,
+    app_state::AppState,
+    game_mode::GameMode,
+    player::{PerPlayer, Player},
+};
+
+const DEFAULT_LIVES: u32 = 3;
+
+#[derive(Debug)]
+pub struct LivesPlugin;
+
+impl Plugin for LivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LivesConfig>().init_resource::<Lives>();
+
+        app.add_observer(reset_lives_on_start_observer)
+            .add_observer(reset_lives_on_restart_observer)
+            .add_observer(ball_lost_observer);
+    }
+}
+
+/// How many lives a player starts a level with.
+#[derive(Debug, Resource)]
+pub struct LivesConfig {
+    pub starting: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Resource, Serialize, Deserialize)]
+pub struct Lives {
+    /// Shared by every player outside of competitive modes.
+    pub remaining: u32,
+    /// The lives of every player in competitive modes, see [`GameMode::is_competitive`].
+    pub per_player: PerPlayer<u32>,
+}
+
+/// Triggered when the last ball left the playfield.
+#[derive(Debug, Event)]
+pub struct BallLost {
+    /// The player who let the ball through. `None` while the players share their lives.
+    pub defender: Option<Player>,
+}
+
+/// Triggered after a life was taken because a ball was lost.
+#[derive(Debug, Event)]
+pub struct LifeLost {
+    pub remaining: u32,
+    /// The player who lost the life. `None` if it was taken from the shared lives.
+    pub player: Option<Player>,
+}
+
+/// Triggered after an extra life was granted.
+#[derive(Debug, Event)]
+pub struct LifeGained {
+    pub remaining: u32,
+    /// The player who gained the life. `None` if it was added to the shared lives.
+    pub player: Option<Player>,
+}
+
+impl Default for LivesConfig {
+    fn default() -> Self {
+        Self {
+            starting: DEFAULT_LIVES,
+        }
+    }
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIVES)
+    }
+}
+
+impl Lives {
+    fn new(starting: u32) -> Self {
+        Self {
+            remaining: starting,
+            per_player: PerPlayer::splat(starting),
+        }
+    }
+
+    /// The lives of `player` in competitive modes, otherwise the shared lives.
+    pub fn of(&self, game_mode: GameMode, player: Option<&Player>) -> u32 {
+        match player {
+            Some(player) if game_mode.is_competitive() => *self.per_player.get(player),
+            _ => self.remaining,
+        }
+    }
+
+    pub fn of_mut(&mut self, game_mode: GameMode, player: Option<&Player>) -> &mut u32 {
+        match player {
+            Some(player) if game_mode.is_competitive() => self.per_player.get_mut(player),
+            _ => &mut self.remaining,
+        }
+    }
+}
+
+fn reset_lives_on_start_observer(
+    _: Trigger<StartGame>,
+    config: Res<LivesConfig>,
+    mut lives: ResMut<Lives>,
+) {
+    *lives = Lives::new(config.starting);
+}
+
+fn reset_lives_on_restart_observer(
+    _: Trigger<RestartLevel>,
+    config: Res<LivesConfig>,
+    mut lives: ResMut<Lives>,
+) {
+    *lives = Lives::new(config.starting);
+}
+
+fn ball_lost_observer(
+    trigger: Trigger<BallLost>,
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    mut lives: ResMut<Lives>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    let player = trigger.defender.clone();
+    let remaining = lives.of_mut(*game_mode, player.as_ref());
+    *remaining = remaining.saturating_sub(1);
+    let remaining = *remaining;
+    info!("Life lost, {remaining} remaining");
+
+    commands.trigger(LifeLost { remaining, player });
+
+    if remaining == 0 {
+        // In versus the round ends as soon as one player is out of lives.
+        app_state.set(if game_mode.is_competitive() {
+            AppState::RoundOver
+        } else {
+            AppState::GameOver
+        });
+    };
+}
@@ -1,71 +1,57 @@
-//! This is a Breakout clone.
-
 use bevy::prelude::*;
 use bevy_embedded_assets::{EmbeddedAssetPlugin, PluginMode};
 
-use crate::{
-    app_state::AppState, ball::BallPlugin, main_menu::MainMenuPlugin, player::PlayerPlugin,
-    world::WorldPlugin,
+use breakout::{
+    BreakoutPlugins,
+    args::Args,
+    headless::HeadlessPlugin,
+    net::{self, NetMode, NetPlugin},
+    replay::ReplayPlugin,
 };
 
-mod app_state;
-mod ball;
-mod main_menu;
-mod player;
-mod world;
+fn main() -> AppExit {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}");
+            return AppExit::error();
+        }
+    };
+    if let (NetMode::Join { host }, true) = (&args.net_mode, args.headless) {
+        return net::headless::run(*host);
+    };
 
-fn main() {
     let mut app = App::new();
 
-    app.add_plugins((
-        EmbeddedAssetPlugin {
-            mode: PluginMode::ReplaceDefault,
-        },
-        DefaultPlugins,
-        avian2d::PhysicsPlugins::default(),
-    ));
-
-    app.init_state::<AppState>();
-
-    app.add_plugins((MainMenuPlugin, WorldPlugin, PlayerPlugin, BallPlugin));
-
-    app.add_observer(start_game_observer)
-        .add_observer(stop_game_observer);
-
-    app.add_systems(
-        Startup,
-        (show_archetypes, |mut commands: Commands| {
+    if args.headless {
+        app.add_plugins(HeadlessPlugin::default());
+    } else {
+        app.add_plugins((
+            EmbeddedAssetPlugin {
+                mode: PluginMode::ReplaceDefault,
+            },
+            DefaultPlugins,
+        ))
+        .add_systems(Startup, |mut commands: Commands| {
             commands.spawn(Camera2d);
-        }),
-    )
-    .add_systems(
-        Update,
-        stop_game_on_esc_system.run_if(in_state(AppState::InGame)),
+        });
+    };
+
+    app.add_plugins(
+        BreakoutPlugins
+            .set(NetPlugin {
+                mode: args.net_mode,
+            })
+            .set(ReplayPlugin {
+                mode: args.replay_mode,
+            }),
     );
 
-    app.run();
+    app.add_systems(Startup, show_archetypes);
+
+    app.run()
 }
 
 fn show_archetypes(world: &World) {
     debug!("Archetypes: {}", world.archetypes().len());
 }
-
-#[derive(Debug, Event)]
-pub struct StartGame;
-
-#[derive(Debug, Event)]
-pub struct StopGame;
-
-fn start_game_observer(_: Trigger<StartGame>, mut app_state: ResMut<NextState<AppState>>) {
-    app_state.set(AppState::InGame);
-}
-
-fn stop_game_observer(_: Trigger<StopGame>, mut app_state: ResMut<NextState<AppState>>) {
-    app_state.set(AppState::MainMenu);
-}
-
-fn stop_game_on_esc_system(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>) {
-    if keys.just_pressed(KeyCode::Escape) {
-        commands.trigger(StopGame);
-    };
-}
@@ -1,6 +1,6 @@
-use bevy::{prelude::*, text::FontSmoothing};
+use bevy::prelude::*;
 
-use crate::{StartGame, app_state::AppState};
+use crate::{StartGame, app_state::AppState, game_mode::GameMode, ui};
 
 pub struct MainMenuPlugin;
 
@@ -10,11 +10,7 @@ impl Plugin for MainMenuPlugin {
             .add_systems(OnExit(AppState::MainMenu), main_menu_cleanup)
             .add_systems(
                 Update,
-                (
-                    update_button_color_system,
-                    play_button_pressed_system,
-                    exit_button_pressed_system,
-                )
+                (play_button_pressed_system, exit_button_pressed_system)
                     .run_if(in_state(AppState::MainMenu)),
             );
     }
@@ -23,97 +19,23 @@ impl Plugin for MainMenuPlugin {
 #[derive(Debug, Component)]
 pub struct MainMenu;
 
+/// Starts the game in the given mode.
 #[derive(Debug, Component)]
-struct StartButton;
+struct StartButton(GameMode);
 
 #[derive(Debug, Component)]
 struct ExitButton;
 
-#[derive(Debug, Clone, Component)]
-struct ButtonColorScheme {
-    normal: Color,
-    hover: Color,
-    pressed: Color,
-}
-
 fn main_menu_setup(mut commands: Commands) {
-    commands.spawn((
-        MainMenu,
-        Node {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            justify_content: JustifyContent::Center,
-            flex_direction: FlexDirection::Column,
-            align_items: AlignItems::Center,
-            row_gap: Val::Px(12.0),
-            ..Default::default()
-        },
-        children![
-            (
-                Text::new("BREAKOUT"),
-                TextFont {
-                    font_size: 64.0,
-                    font_smoothing: FontSmoothing::AntiAliased,
-                    ..Default::default()
-                },
-                TextColor(Color::WHITE),
-            ),
-            (
-                Node {
-                    flex_direction: FlexDirection::Column,
-                    align_items: AlignItems::Center,
-                    row_gap: Val::Px(12.0),
-                    ..Default::default()
-                },
-                children![
-                    (
-                        Button,
-                        StartButton,
-                        ButtonColorScheme::default(),
-                        Node {
-                            width: Val::Px(220.0),
-                            height: Val::Px(60.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            ..Default::default()
-                        },
-                        BackgroundColor(Color::linear_rgb(0.3, 0.3, 0.3)),
-                        children![(
-                            Text::new("Play"),
-                            TextFont {
-                                font_size: 32.0,
-                                font_smoothing: FontSmoothing::AntiAliased,
-                                ..Default::default()
-                            },
-                            TextColor(Color::WHITE),
-                        )],
-                    ),
-                    (
-                        Button,
-                        ExitButton,
-                        ButtonColorScheme::default(),
-                        Node {
-                            width: Val::Px(220.0),
-                            height: Val::Px(60.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            ..Default::default()
-                        },
-                        BackgroundColor(Color::linear_rgb(0.3, 0.3, 0.3)),
-                        children![(
-                            Text::new("Exit"),
-                            TextFont {
-                                font_size: 32.0,
-                                font_smoothing: FontSmoothing::AntiAliased,
-                                ..Default::default()
-                            },
-                            TextColor(Color::WHITE)
-                        )],
-                    ),
-                ]
-            )
-        ],
-    ));
+    let menu = commands
+        .spawn((MainMenu, ui::screen(), children![ui::title("BREAKOUT")]))
+        .id();
+    let buttons = commands.spawn((ChildOf(menu), ui::button_column())).id();
+
+    for mode in GameMode::ALL {
+        commands.spawn((ChildOf(buttons), ui::button(mode.name()), StartButton(mode)));
+    }
+    commands.spawn((ChildOf(buttons), ui::button("Exit"), ExitButton));
 }
 
 fn main_menu_cleanup(mut commands: Commands, main_menu: Query<Entity, With<MainMenu>>) {
@@ -122,33 +44,15 @@ fn main_menu_cleanup(mut commands: Commands, main_menu: Query<Entity, With<MainM
     }
 }
 
-fn update_button_color_system(
-    mut buttons: Query<
-        (&Interaction, &mut BackgroundColor, &ButtonColorScheme),
-        (Changed<Interaction>, With<Button>),
-    >,
-) {
-    for (interaction, mut bg_color, btn_color_scheme) in buttons.iter_mut() {
-        match interaction {
-            Interaction::None => {
-                bg_color.0 = btn_color_scheme.normal;
-            }
-            Interaction::Hovered => {
-                bg_color.0 = btn_color_scheme.hover;
-            }
-            Interaction::Pressed => {
-                bg_color.0 = btn_color_scheme.pressed;
-            }
-        };
-    }
-}
-
 fn play_button_pressed_system(
     mut commands: Commands,
-    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<StartButton>)>,
+    mut game_mode: ResMut<GameMode>,
+    buttons: Query<(&Interaction, &StartButton), (Changed<Interaction>, With<Button>)>,
 ) {
-    for interaction in buttons.iter() {
+    for (interaction, button) in buttons.iter() {
         if *interaction == Interaction::Pressed {
+            *game_mode = button.0;
+            info!("Starting a {} game", button.0.name());
             commands.trigger(StartGame);
         };
     }
@@ -164,13 +68,3 @@ fn exit_button_pressed_system(
         };
     }
 }
-
-impl Default for ButtonColorScheme {
-    fn default() -> Self {
-        Self {
-            normal: Color::linear_rgb(0.3, 0.3, 0.3),
-            hover: Color::linear_rgb(0.275, 0.275, 0.275),
-            pressed: Color::linear_rgb(0.2, 0.2, 0.2),
-        }
-    }
-}
@@ -0,0 +1,91 @@
+//! Networked two-player mode. The host runs the game as usual and [`Player::Second`] is played by
+//! a client on another machine. The client only sends its paddle input and draws the snapshots it
+//! receives, the host simulates everything else.
+//!
+//! Start the host with `--host [PORT]` and the client with `--join [ADDRESS]`. Both default to
+//! port 7777 on 127.0.0.1, so both can run on one machine. `--join [ADDRESS] --headless` runs a
+//! client without a window that plays with scripted input and logs the snapshots.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    args::ArgsError,
+    net::{client::ClientPlugin, host::HostPlugin},
+    player::Player,
+};
+
+pub mod client;
+pub mod headless;
+pub mod host;
+pub mod protocol;
+pub mod socket;
+mod view;
+
+pub const DEFAULT_PORT: u16 = 7777;
+
+/// How often the client repeats its hello until the host answers.
+const HANDSHAKE_INTERVAL: Duration = Duration::from_millis(500);
+/// A peer that sent nothing for this long is considered gone.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The player the client controls on the host.
+const REMOTE_PLAYER: Player = Player::Second;
+
+/// Adds the host or client side, depending on the [`NetMode`].
+#[derive(Debug, Default)]
+pub struct NetPlugin {
+    pub mode: NetMode,
+}
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.mode.clone());
+
+        match self.mode {
+            NetMode::Local => (),
+            NetMode::Host { port } => {
+                app.add_plugins(HostPlugin { port });
+            }
+            NetMode::Join { host } => {
+                app.add_plugins(ClientPlugin { host });
+            }
+        };
+    }
+}
+
+/// Whether this game hosts or joins a networked game, read from the command line.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Resource)]
+pub enum NetMode {
+    /// Both players share this machine.
+    #[default]
+    Local,
+    /// Runs the game and lets a client play [`Player::Second`].
+    Host { port: u16 },
+    /// Plays [`Player::Second`] in the game of `host`.
+    Join { host: SocketAddr },
+}
+
+/// The address of the client's own socket, matching the address family of `host`.
+fn unspecified_address(host: &SocketAddr) -> SocketAddr {
+    let ip = match host.ip() {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+
+    SocketAddr::new(ip, 0)
+}
+
+/// Resolves `host:port`, or `host` with the [`DEFAULT_PORT`].
+pub fn resolve(address: &str) -> Result<SocketAddr, ArgsError> {
+    address
+        .to_socket_addrs()
+        .or_else(|_| (address, DEFAULT_PORT).to_socket_addrs())
+        .ok()
+        .and_then(|mut addresses| addresses.next())
+        .ok_or_else(|| ArgsError::InvalidAddress(address.to_string()))
+}
@@ -0,0 +1,465 @@
+use std::{net::SocketAddr, time::Instant};
+
+use bevy::prelude::*;
+
+use crate::{
+    app_state::AppState,
+    gamepad::{GamepadConfig, LAUNCH_BUTTON, PlayerGamepad},
+    input::{Action, InputBindings},
+    net::{
+        HANDSHAKE_INTERVAL, TIMEOUT,
+        protocol::{
+            BlockState, BlockStates, ClientMessage, HostMessage, LayoutBlock, LayoutChunk,
+            PROTOCOL_VERSION, RejectReason, RemoteInput, Snapshot,
+        },
+        socket::{NetError, NetSocket},
+        unspecified_address,
+        view::ViewPlugin,
+    },
+    player::Player,
+    ui,
+};
+
+/// The local keys and gamepad that steer the remote paddle.
+const LOCAL_PLAYER: Player = Player::First;
+
+/// Joins the game of `host` and plays it in [`AppState::RemoteGame`].
+#[derive(Debug)]
+pub struct ClientPlugin {
+    pub host: SocketAddr,
+}
+
+impl Plugin for ClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ViewPlugin);
+
+        app.insert_resource(JoinAddress(self.host));
+
+        app.add_systems(Startup, join_on_start_system)
+            .add_systems(
+                OnEnter(AppState::MainMenu),
+                (join_bar_setup, close_client_system),
+            )
+            .add_systems(OnExit(AppState::MainMenu), ui::despawn_screen::<JoinBar>)
+            .add_systems(OnEnter(AppState::Connecting), connecting_setup)
+            .add_systems(
+                OnExit(AppState::Connecting),
+                ui::despawn_screen::<ConnectingScreen>,
+            )
+            .add_systems(
+                Update,
+                (
+                    join_button_pressed_system.run_if(in_state(AppState::MainMenu)),
+                    cancel_button_pressed_system.run_if(in_state(AppState::Connecting)),
+                    (send_input_system, leave_system).run_if(in_state(AppState::RemoteGame)),
+                    client_update_system.run_if(resource_exists::<Client>),
+                ),
+            )
+            .add_systems(
+                Last,
+                say_goodbye_system.run_if(resource_exists::<Client>.and(on_event::<AppExit>)),
+            );
+    }
+}
+
+/// The host given on the command line.
+#[derive(Debug, Resource)]
+struct JoinAddress(SocketAddr);
+
+/// The connection to the host. Exists from [`AppState::Connecting`] until the client is back in
+/// the main menu.
+#[derive(Debug, Resource)]
+pub struct Client {
+    socket: NetSocket,
+    host: SocketAddr,
+    connected: bool,
+    started: Instant,
+    last_hello: Option<Instant>,
+    last_received: Instant,
+    last_snapshot: u32,
+    input: RemoteInput,
+    layout: Option<ReceivedLayout>,
+}
+
+/// The layout of the host's blocks, put together from its chunks.
+#[derive(Debug)]
+struct ReceivedLayout {
+    id: u32,
+    block_size: [f32; 2],
+    blocks: Vec<Option<LayoutBlock>>,
+}
+
+/// What happened since the last [`Client::update`].
+#[derive(Debug)]
+pub enum ClientEvent {
+    Connected,
+    Rejected(RejectReason),
+    /// The newest snapshot that arrived.
+    Snapshot(Box<Snapshot>),
+    HostLeft,
+    TimedOut,
+}
+
+/// The newest snapshot of the host's game.
+#[derive(Debug, Resource)]
+pub struct LatestSnapshot {
+    pub snapshot: Snapshot,
+    /// The blocks of the snapshot, see [`Client::blocks`].
+    pub blocks: Vec<BlockState>,
+}
+
+#[derive(Debug, Component)]
+struct JoinBar;
+
+#[derive(Debug, Component)]
+struct JoinButton;
+
+#[derive(Debug, Component)]
+struct ConnectingScreen;
+
+#[derive(Debug, Component)]
+struct CancelButton;
+
+impl Client {
+    /// Opens a socket for talking to `host`. The handshake starts with the first
+    /// [`Client::update`].
+    pub fn connect(host: SocketAddr) -> Result<Self, NetError> {
+        let socket = NetSocket::bind(unspecified_address(&host))?;
+        info!("Joining {host} from {}", socket.local_addr()?);
+
+        let now = Instant::now();
+        Ok(Self {
+            socket,
+            host,
+            connected: false,
+            started: now,
+            last_hello: None,
+            last_received: now,
+            last_snapshot: 0,
+            input: RemoteInput::default(),
+            layout: None,
+        })
+    }
+
+    pub fn host(&self) -> SocketAddr {
+        self.host
+    }
+
+    /// Whether the host answered the handshake.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Receives the messages of the host, repeats the hello until the host answers and checks for
+    /// timeouts.
+    pub fn update(&mut self) -> Vec<ClientEvent> {
+        let mut events = Vec::new();
+        let mut snapshot = None;
+
+        for (from, message) in self.socket.receive::<HostMessage>() {
+            // Nobody else has any business talking to the client.
+            if from != self.host {
+                continue;
+            };
+            self.last_received = Instant::now();
+
+            match message {
+                HostMessage::Welcome => self.set_connected(&mut events),
+                HostMessage::Rejected(reason) => events.push(ClientEvent::Rejected(reason)),
+                HostMessage::Snapshot(received) => {
+                    // Snapshots only come after the welcome, so one arriving means it got lost.
+                    self.set_connected(&mut events);
+                    if received.sequence > self.last_snapshot {
+                        self.last_snapshot = received.sequence;
+                        snapshot = Some(received);
+                    };
+                }
+                HostMessage::Layout(chunk) => self.receive_layout(chunk),
+                HostMessage::Disconnect => events.push(ClientEvent::HostLeft),
+            };
+        }
+
+        if let Some(snapshot) = snapshot {
+            events.push(ClientEvent::Snapshot(Box::new(snapshot)));
+        };
+
+        if self.connected {
+            if self.last_received.elapsed() > TIMEOUT {
+                events.push(ClientEvent::TimedOut);
+            };
+        } else if self.started.elapsed() > TIMEOUT {
+            events.push(ClientEvent::TimedOut);
+        } else if self
+            .last_hello
+            .is_none_or(|last_hello| last_hello.elapsed() >= HANDSHAKE_INTERVAL)
+        {
+            self.last_hello = Some(Instant::now());
+            self.send(&ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+            });
+        };
+
+        events
+    }
+
+    /// Sends the paddle input for this frame.
+    pub fn send_input(&mut self, axis: f32, launch: bool) {
+        self.input.sequence += 1;
+        self.input.axis = axis;
+        if launch {
+            self.input.launches += 1;
+        };
+
+        self.send(&ClientMessage::Input(self.input));
+    }
+
+    pub fn disconnect(&self) {
+        self.send(&ClientMessage::Disconnect);
+    }
+
+    /// The blocks that are left according to `states`. Empty until the whole layout arrived.
+    pub fn blocks(&self, states: &BlockStates) -> Vec<BlockState> {
+        let Some(layout) = self
+            .layout
+            .as_ref()
+            .filter(|layout| layout.id == states.layout)
+        else {
+            return Vec::new();
+        };
+
+        layout
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| states.is_remaining(*index))
+            .filter_map(|(index, block)| {
+                let block = (*block)?;
+                let color = states
+                    .damaged
+                    .iter()
+                    .find(|damaged| usize::from(damaged.index) == index)
+                    .map_or(block.color, |damaged| damaged.color);
+
+                Some(BlockState {
+                    position: block.position,
+                    size: layout.block_size,
+                    color,
+                })
+            })
+            .collect()
+    }
+
+    fn receive_layout(&mut self, chunk: LayoutChunk) {
+        let current = self.layout.as_ref().map_or(0, |layout| layout.id);
+        // Chunks of older layouts can arrive late.
+        if chunk.layout < current {
+            return;
+        };
+        if chunk.layout > current {
+            self.layout = Some(ReceivedLayout {
+                id: chunk.layout,
+                block_size: chunk.block_size,
+                blocks: vec![None; usize::from(chunk.block_count)],
+            });
+        };
+        let Some(layout) = self.layout.as_mut() else {
+            return;
+        };
+
+        for (offset, block) in chunk.blocks.into_iter().enumerate() {
+            if let Some(slot) = layout.blocks.get_mut(usize::from(chunk.first) + offset) {
+                *slot = Some(block);
+            };
+        }
+
+        if layout.blocks.iter().all(Option::is_some) {
+            self.input.layout = layout.id;
+        };
+    }
+
+    fn set_connected(&mut self, events: &mut Vec<ClientEvent>) {
+        if !self.connected {
+            self.connected = true;
+            events.push(ClientEvent::Connected);
+        };
+    }
+
+    fn send(&self, message: &ClientMessage) {
+        if let Err(error) = self.socket.send(self.host, message) {
+            warn!("Could not send to {}: {error}", self.host);
+        };
+    }
+}
+
+fn join_on_start_system(mut app_state: ResMut<NextState<AppState>>) {
+    app_state.set(AppState::Connecting);
+}
+
+fn join_bar_setup(mut commands: Commands, join_address: Res<JoinAddress>) {
+    commands.spawn((
+        JoinBar,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(24.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..Default::default()
+        },
+        children![
+            ui::label(format!("Host: {}", join_address.0)),
+            (ui::button("Join"), JoinButton),
+        ],
+    ));
+}
+
+/// Back in the main menu the connection is over, however it ended.
+fn close_client_system(mut commands: Commands) {
+    commands.remove_resource::<Client>();
+    commands.remove_resource::<LatestSnapshot>();
+}
+
+fn connecting_setup(
+    mut commands: Commands,
+    join_address: Res<JoinAddress>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    match Client::connect(join_address.0) {
+        Ok(client) => commands.insert_resource(client),
+        Err(error) => {
+            error!("Could not join {}: {error}", join_address.0);
+            app_state.set(AppState::MainMenu);
+            return;
+        }
+    };
+
+    commands.spawn((
+        ConnectingScreen,
+        ui::screen(),
+        children![
+            ui::title("CONNECTING"),
+            ui::label(format!("Joining {}", join_address.0)),
+            (ui::button("Cancel"), CancelButton),
+        ],
+    ));
+}
+
+fn join_button_pressed_system(
+    mut app_state: ResMut<NextState<AppState>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<JoinButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            app_state.set(AppState::Connecting);
+        };
+    }
+}
+
+fn cancel_button_pressed_system(
+    client: Option<Res<Client>>,
+    mut app_state: ResMut<NextState<AppState>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<CancelButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            if let Some(client) = &client {
+                client.disconnect();
+            };
+            app_state.set(AppState::MainMenu);
+        };
+    }
+}
+
+fn client_update_system(
+    mut commands: Commands,
+    mut client: ResMut<Client>,
+    app_state: Res<State<AppState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    for event in client.update() {
+        match event {
+            ClientEvent::Connected => {
+                info!("Joined {}", client.host());
+                if *app_state.get() == AppState::Connecting {
+                    next_app_state.set(AppState::RemoteGame);
+                };
+            }
+            ClientEvent::Snapshot(snapshot) => {
+                let blocks = client.blocks(&snapshot.blocks);
+                commands.insert_resource(LatestSnapshot {
+                    snapshot: *snapshot,
+                    blocks,
+                });
+            }
+            ClientEvent::Rejected(reason) => {
+                error!(
+                    "{} rejected the connection, {}",
+                    client.host(),
+                    reason.description()
+                );
+                next_app_state.set(AppState::MainMenu);
+            }
+            ClientEvent::HostLeft => {
+                info!("{} closed the game", client.host());
+                next_app_state.set(AppState::MainMenu);
+            }
+            ClientEvent::TimedOut => {
+                warn!("{} timed out", client.host());
+                next_app_state.set(AppState::MainMenu);
+            }
+        };
+    }
+}
+
+fn send_input_system(
+    mut client: ResMut<Client>,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<InputBindings>,
+    gamepad_config: Res<GamepadConfig>,
+    gamepads: Query<(&Gamepad, &PlayerGamepad)>,
+) {
+    let keyboard_axis = f32::from(
+        i8::from(bindings.pressed(&keys, &LOCAL_PLAYER, Action::MoveRight))
+            - i8::from(bindings.pressed(&keys, &LOCAL_PLAYER, Action::MoveLeft)),
+    );
+    let local_gamepads = gamepads
+        .iter()
+        .filter(|(_, assigned)| assigned.0 == LOCAL_PLAYER);
+    let gamepad_axis = local_gamepads
+        .clone()
+        .map(|(gamepad, _)| gamepad_config.horizontal_axis(gamepad))
+        .sum::<f32>();
+
+    let launch = bindings.just_pressed(&keys, &LOCAL_PLAYER, Action::Launch)
+        || local_gamepads
+            .clone()
+            .any(|(gamepad, _)| gamepad.just_pressed(LAUNCH_BUTTON));
+
+    client.send_input((keyboard_axis + gamepad_axis).clamp(-1.0, 1.0), launch);
+}
+
+/// The pause key leaves the game, only the host can pause it.
+fn leave_system(
+    client: Res<Client>,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<InputBindings>,
+    gamepads: Query<(&Gamepad, &PlayerGamepad)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    let leave = bindings.just_pressed(&keys, &LOCAL_PLAYER, Action::Pause)
+        || gamepads.iter().any(|(gamepad, assigned)| {
+            assigned.0 == LOCAL_PLAYER && gamepad.just_pressed(GamepadButton::Start)
+        });
+
+    if leave {
+        info!("Left the game of {}", client.host());
+        client.disconnect();
+        app_state.set(AppState::MainMenu);
+    };
+}
+
+fn say_goodbye_system(client: Res<Client>) {
+    client.disconnect();
+}
@@ -0,0 +1,119 @@
+//! A client without a window for trying the networked mode on one machine. It joins the host,
+//! sweeps its paddle from side to side, launches now and then and logs the snapshots it receives.
+
+use std::{net::SocketAddr, time::Duration};
+
+use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin, prelude::*};
+
+use crate::net::client::{Client, ClientEvent};
+
+const FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+const SWEEP_TIME: Duration = Duration::from_secs(2);
+const LAUNCH_INTERVAL: Duration = Duration::from_secs(3);
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Runs the headless client until the host closes the game or stops answering.
+pub fn run(host: SocketAddr) -> AppExit {
+    App::new()
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(FRAME_TIME)),
+            LogPlugin::default(),
+        ))
+        .insert_resource(HeadlessHost(host))
+        .insert_resource(Script {
+            sweep: Timer::new(SWEEP_TIME, TimerMode::Repeating),
+            launch: Timer::new(LAUNCH_INTERVAL, TimerMode::Repeating),
+            report: Timer::new(REPORT_INTERVAL, TimerMode::Repeating),
+            direction: 1.0,
+        })
+        .add_systems(Startup, connect_system)
+        .add_systems(
+            Update,
+            (receive_system, play_system)
+                .chain()
+                .run_if(resource_exists::<Client>),
+        )
+        .run()
+}
+
+#[derive(Debug, Resource)]
+struct HeadlessHost(SocketAddr);
+
+/// The scripted input of the headless client.
+#[derive(Debug, Resource)]
+struct Script {
+    /// Turns the paddle around when it finishes.
+    sweep: Timer,
+    launch: Timer,
+    /// Logs the latest snapshot when it finishes.
+    report: Timer,
+    direction: f32,
+}
+
+fn connect_system(mut commands: Commands, host: Res<HeadlessHost>, mut exit: EventWriter<AppExit>) {
+    match Client::connect(host.0) {
+        Ok(client) => commands.insert_resource(client),
+        Err(error) => {
+            error!("Could not join {}: {error}", host.0);
+            exit.write(AppExit::error());
+        }
+    };
+}
+
+fn receive_system(
+    time: Res<Time>,
+    mut client: ResMut<Client>,
+    mut script: ResMut<Script>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let report = script.report.tick(time.delta()).just_finished();
+
+    for event in client.update() {
+        match event {
+            ClientEvent::Connected => info!("Joined {}", client.host()),
+            ClientEvent::Snapshot(snapshot) => {
+                if report {
+                    info!(
+                        "Snapshot {}: {:?}, {} paddles, {} balls, {} blocks, {} capsules, {} points",
+                        snapshot.sequence,
+                        snapshot.phase,
+                        snapshot.paddles.len(),
+                        snapshot.balls.len(),
+                        snapshot.blocks.remaining_count(),
+                        snapshot.capsules.len(),
+                        snapshot.points
+                    );
+                };
+            }
+            ClientEvent::Rejected(reason) => {
+                error!(
+                    "{} rejected the connection, {}",
+                    client.host(),
+                    reason.description()
+                );
+                exit.write(AppExit::error());
+            }
+            ClientEvent::HostLeft => {
+                info!("{} closed the game", client.host());
+                exit.write(AppExit::Success);
+            }
+            ClientEvent::TimedOut => {
+                error!("{} timed out", client.host());
+                exit.write(AppExit::error());
+            }
+        };
+    }
+}
+
+fn play_system(time: Res<Time>, mut client: ResMut<Client>, mut script: ResMut<Script>) {
+    if !client.is_connected() {
+        return;
+    };
+
+    if script.sweep.tick(time.delta()).just_finished() {
+        script.direction = -script.direction;
+    };
+    let launch = script.launch.tick(time.delta()).just_finished();
+
+    client.send_input(script.direction, launch);
+}
@@ -0,0 +1,495 @@
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    StopGame,
+    app_state::{AppState, GameState},
+    ball::Ball,
+    game_mode::GameMode,
+    input::{PlayerInputs, ReadInput, read_devices_system},
+    lives::Lives,
+    net::{
+        REMOTE_PLAYER, TIMEOUT,
+        protocol::{
+            BlockStates, CapsuleState, ClientMessage, DamagedBlock, HostMessage, LayoutBlock,
+            LayoutChunk, PROTOCOL_VERSION, PaddleState, Phase, PlayerState, RejectReason,
+            RemoteInput, Snapshot,
+        },
+        socket::NetSocket,
+    },
+    player::{Player, PlayerSize},
+    power_up::Capsule,
+    score::Score,
+    ui, versus,
+    world::{
+        Playfield,
+        block::{Block, BlockSize},
+        level::{CurrentLevel, Level},
+    },
+};
+
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(33);
+/// Keeps every [`LayoutChunk`] around 1 KB, within the
+/// [`DATAGRAM_BUDGET`](super::socket::DATAGRAM_BUDGET).
+const LAYOUT_CHUNK_BLOCKS: usize = 40;
+
+/// Accepts one client that plays [`REMOTE_PLAYER`] and sends it snapshots of the game. The app
+/// exits with an error if it cannot host. Port 0 picks a free port, see [`Host::port`].
+#[derive(Debug)]
+pub struct HostPlugin {
+    pub port: u16,
+}
+
+impl Plugin for HostPlugin {
+    fn build(&self, app: &mut App) {
+        let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, self.port));
+        match NetSocket::bind(address) {
+            Ok(socket) => {
+                let port = socket
+                    .local_addr()
+                    .map_or(self.port, |address| address.port());
+                info!("Hosting on port {port}");
+                app.insert_resource(Host::new(socket, port));
+            }
+            Err(error) => {
+                // The client waits for a host that will never answer, so do not play on alone.
+                error!("Could not host on port {}: {error}", self.port);
+                app.add_systems(Startup, |mut exit: EventWriter<AppExit>| {
+                    exit.write(AppExit::error());
+                });
+                return;
+            }
+        };
+
+        app.init_resource::<RemotePaddle>();
+
+        app.add_systems(OnEnter(AppState::MainMenu), host_status_setup)
+            .add_systems(OnExit(AppState::MainMenu), ui::despawn_screen::<HostStatus>)
+            .add_systems(PreUpdate, receive_system)
+            .add_systems(
+                Update,
+                update_host_status_system
+                    .run_if(resource_changed::<Host>.and(in_state(AppState::MainMenu))),
+            )
+            .add_systems(
+                RunFixedMainLoop,
+                remote_input_system
+                    .after(read_devices_system)
+                    .in_set(ReadInput),
+            )
+            .add_systems(
+                PostUpdate,
+                (build_layout_system, send_snapshot_system).chain(),
+            )
+            .add_systems(Last, say_goodbye_system.run_if(on_event::<AppExit>));
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct Host {
+    socket: NetSocket,
+    port: u16,
+    peer: Option<Peer>,
+    snapshot_timer: Timer,
+    sequence: u32,
+    layout: Option<Layout>,
+}
+
+#[derive(Debug)]
+struct Peer {
+    address: SocketAddr,
+    last_received: Instant,
+    last_input: u32,
+    launches: u32,
+    /// The layout the client has.
+    layout: u32,
+}
+
+/// Where the blocks of the current level are, as sent to the client.
+#[derive(Debug)]
+struct Layout {
+    id: u32,
+    chunks: Vec<LayoutChunk>,
+    indices: HashMap<Entity, u16>,
+    /// The color of every block when the layout was made, to tell which ones changed since.
+    colors: Vec<[f32; 4]>,
+}
+
+/// The latest input of the client for the paddle of [`REMOTE_PLAYER`].
+#[derive(Debug, Default, Resource)]
+pub struct RemotePaddle {
+    pub axis: f32,
+    /// The client pressed launch since the last frame.
+    pub launch: bool,
+}
+
+/// Everything the client draws.
+#[derive(SystemParam)]
+struct Shapes<'w, 's> {
+    paddles: Query<'w, 's, (&'static Player, &'static Transform)>,
+    balls: Query<'w, 's, &'static Transform, With<Ball>>,
+    blocks: Query<'w, 's, (Entity, &'static MeshMaterial2d<ColorMaterial>), With<Block>>,
+    capsules:
+        Query<'w, 's, (&'static Transform, &'static MeshMaterial2d<ColorMaterial>), With<Capsule>>,
+}
+
+/// Shows the port and whether a client joined in the main menu.
+#[derive(Debug, Component)]
+struct HostStatus;
+
+#[derive(Debug, Component)]
+struct HostStatusText;
+
+impl Host {
+    fn new(socket: NetSocket, port: u16) -> Self {
+        Self {
+            socket,
+            port,
+            peer: None,
+            snapshot_timer: Timer::new(SNAPSHOT_INTERVAL, TimerMode::Repeating),
+            sequence: 0,
+            layout: None,
+        }
+    }
+
+    /// The port the host listens on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn peer_address(&self) -> Option<SocketAddr> {
+        self.peer.as_ref().map(|peer| peer.address)
+    }
+
+    fn send(&self, to: SocketAddr, message: &HostMessage) {
+        if let Err(error) = self.socket.send(to, message) {
+            warn!("Could not send to {to}: {error}");
+        };
+    }
+
+    fn handshake(&mut self, from: SocketAddr, version: u32) {
+        if version != PROTOCOL_VERSION {
+            info!("Rejected {from}, it uses protocol version {version}");
+            let reason = RejectReason::VersionMismatch {
+                host: PROTOCOL_VERSION,
+            };
+            self.send(from, &HostMessage::Rejected(reason));
+            return;
+        };
+
+        match &self.peer {
+            // The client repeats its hello until the welcome arrives.
+            Some(peer) if peer.address == from => (),
+            Some(_) => {
+                info!("Rejected {from}, another client already joined");
+                self.send(from, &HostMessage::Rejected(RejectReason::Full));
+                return;
+            }
+            None => {
+                info!("{from} joined as {}", REMOTE_PLAYER.short_name());
+                self.peer = Some(Peer {
+                    address: from,
+                    last_received: Instant::now(),
+                    last_input: 0,
+                    launches: 0,
+                    layout: 0,
+                });
+            }
+        };
+
+        self.send(from, &HostMessage::Welcome);
+    }
+}
+
+fn host_status_setup(mut commands: Commands, host: Res<Host>) {
+    commands.spawn((
+        HostStatus,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            padding: UiRect::all(Val::Px(8.0)),
+            ..Default::default()
+        },
+        children![(ui::label(host_status_text(&host)), HostStatusText)],
+    ));
+}
+
+fn update_host_status_system(host: Res<Host>, mut texts: Query<&mut Text, With<HostStatusText>>) {
+    for mut text in texts.iter_mut() {
+        text.0 = host_status_text(&host);
+    }
+}
+
+fn host_status_text(host: &Host) -> String {
+    match host.peer_address() {
+        Some(address) => format!("{} joined from {address}", REMOTE_PLAYER.short_name()),
+        None => format!(
+            "Waiting for {} on port {}",
+            REMOTE_PLAYER.short_name(),
+            host.port
+        ),
+    }
+}
+
+fn receive_system(
+    mut commands: Commands,
+    mut host: ResMut<Host>,
+    mut remote_paddle: ResMut<RemotePaddle>,
+    game_mode: Res<GameMode>,
+    app_state: Res<State<AppState>>,
+) {
+    remote_paddle.launch = false;
+
+    let mut left = false;
+    for (from, message) in host.socket.receive::<ClientMessage>() {
+        if let Some(peer) = host.peer.as_mut().filter(|peer| peer.address == from) {
+            peer.last_received = Instant::now();
+        };
+
+        match message {
+            ClientMessage::Hello { version } => host.handshake(from, version),
+            ClientMessage::Input(input) => {
+                if let Some(peer) = host.peer.as_mut().filter(|peer| peer.address == from) {
+                    apply_input(peer, &input, &mut remote_paddle);
+                };
+            }
+            ClientMessage::Disconnect => {
+                if host.peer_address() == Some(from) {
+                    info!("{from} left");
+                    left = true;
+                };
+            }
+        };
+    }
+
+    if let Some(peer) = host
+        .peer
+        .as_ref()
+        .filter(|peer| peer.last_received.elapsed() > TIMEOUT)
+    {
+        warn!("{} timed out", peer.address);
+        left = true;
+    };
+
+    if !left {
+        return;
+    };
+
+    host.peer = None;
+    *remote_paddle = RemotePaddle::default();
+
+    // The game can not go on without the remote paddle.
+    if *app_state.get() != AppState::MainMenu && game_mode.has_player(&REMOTE_PLAYER) {
+        commands.trigger(StopGame);
+    };
+}
+
+fn apply_input(peer: &mut Peer, input: &RemoteInput, remote_paddle: &mut RemotePaddle) {
+    // Late inputs are older than what the paddle already did.
+    if input.sequence <= peer.last_input {
+        return;
+    };
+    peer.last_input = input.sequence;
+    peer.layout = input.layout;
+
+    remote_paddle.axis = if input.axis.is_finite() {
+        input.axis.clamp(-1.0, 1.0)
+    } else {
+        0.0
+    };
+    if input.launches != peer.launches {
+        peer.launches = input.launches;
+        remote_paddle.launch = true;
+    };
+}
+
+/// The client steers [`REMOTE_PLAYER`] alongside the local keys and gamepad of that player.
+fn remote_input_system(remote_paddle: Res<RemotePaddle>, mut inputs: ResMut<PlayerInputs>) {
+    let input = inputs.0.get_mut(&REMOTE_PLAYER);
+    input.axis = (input.axis + remote_paddle.axis).clamp(-1.0, 1.0);
+    input.launch |= remote_paddle.launch;
+}
+
+/// Makes a new layout whenever blocks spawn, i.e. when a level is loaded.
+fn build_layout_system(
+    mut host: ResMut<Host>,
+    block_size: Option<Res<BlockSize>>,
+    materials: Res<Assets<ColorMaterial>>,
+    blocks: Query<(Entity, &Transform, &MeshMaterial2d<ColorMaterial>), With<Block>>,
+    added: Query<(), Added<Block>>,
+) {
+    if added.is_empty() {
+        return;
+    };
+
+    let id = host.layout.as_ref().map_or(1, |layout| layout.id + 1);
+    let block_size = block_size.map_or(Vec2::ZERO, |block_size| block_size.0);
+    let layout_blocks = blocks
+        .iter()
+        .take(usize::from(u16::MAX))
+        .map(|(entity, transform, material)| {
+            let block = LayoutBlock {
+                position: transform.translation.truncate().to_array(),
+                color: linear_color(&materials, material),
+            };
+            (entity, block)
+        })
+        .collect::<Vec<_>>();
+    let block_count = layout_blocks.len() as u16;
+
+    host.layout = Some(Layout {
+        id,
+        chunks: layout_blocks
+            .chunks(LAYOUT_CHUNK_BLOCKS)
+            .enumerate()
+            .map(|(index, chunk)| LayoutChunk {
+                layout: id,
+                block_size: block_size.to_array(),
+                block_count,
+                first: (index * LAYOUT_CHUNK_BLOCKS) as u16,
+                blocks: chunk.iter().map(|(_, block)| *block).collect(),
+            })
+            .collect(),
+        indices: layout_blocks
+            .iter()
+            .enumerate()
+            .map(|(index, (entity, _))| (*entity, index as u16))
+            .collect(),
+        colors: layout_blocks.iter().map(|(_, block)| block.color).collect(),
+    });
+}
+
+fn send_snapshot_system(
+    time: Res<Time<Real>>,
+    mut host: ResMut<Host>,
+    app_state: Res<State<AppState>>,
+    game_state: Option<Res<State<GameState>>>,
+    game_mode: Res<GameMode>,
+    score: Res<Score>,
+    lives: Res<Lives>,
+    player_size: Res<PlayerSize>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<Level>>,
+    materials: Res<Assets<ColorMaterial>>,
+    playfield: Res<Playfield>,
+    shapes: Shapes,
+) {
+    if !host.snapshot_timer.tick(time.delta()).just_finished() {
+        return;
+    };
+    let Some(peer) = host.peer.as_ref() else {
+        return;
+    };
+    let peer_address = peer.address;
+
+    // Until the client confirms it has the layout, it gets the layout with every snapshot.
+    if let Some(layout) = host
+        .layout
+        .as_ref()
+        .filter(|layout| layout.id != peer.layout)
+    {
+        for chunk in &layout.chunks {
+            host.send(peer_address, &HostMessage::Layout(chunk.clone()));
+        }
+    };
+
+    let phase = match app_state.get() {
+        AppState::InGame => match game_state.as_deref().map(State::get) {
+            Some(GameState::Paused) => Phase::Paused,
+            _ => Phase::Playing,
+        },
+        AppState::LevelComplete => Phase::LevelComplete,
+        AppState::CampaignWon => Phase::CampaignWon,
+        AppState::GameOver => Phase::GameOver,
+        AppState::RoundOver => Phase::RoundOver {
+            winner: versus::winner(&score, &lives),
+        },
+        AppState::MainMenu
+        | AppState::LoadingLevel
+        | AppState::Connecting
+        | AppState::RemoteGame => Phase::Waiting,
+    };
+    let in_game = phase != Phase::Waiting;
+
+    let mut blocks = BlockStates::default();
+    if let Some(layout) = &host.layout {
+        blocks.layout = layout.id;
+        for (entity, material) in shapes.blocks.iter() {
+            let Some(&index) = layout.indices.get(&entity) else {
+                continue;
+            };
+
+            blocks.set_remaining(usize::from(index));
+            let color = linear_color(&materials, material);
+            if color != layout.colors[usize::from(index)] {
+                blocks.damaged.push(DamagedBlock { index, color });
+            };
+        }
+    };
+
+    host.sequence += 1;
+    let snapshot = Snapshot {
+        sequence: host.sequence,
+        phase,
+        game_mode: *game_mode,
+        level_name: current_level
+            .and_then(|current_level| levels.get(&current_level.0))
+            .filter(|_| in_game)
+            .map(|level| level.name.clone())
+            .unwrap_or_default(),
+        field_size: playfield.size.to_array(),
+        points: score.points,
+        lives: lives.remaining,
+        per_player: [Player::First, Player::Second].map(|player| PlayerState {
+            points: *score.per_player.get(&player),
+            lives: *lives.per_player.get(&player),
+        }),
+        paddles: shapes
+            .paddles
+            .iter()
+            .map(|(player, transform)| PaddleState {
+                player: player.clone(),
+                position: transform.translation.truncate().to_array(),
+                size: [player_size.x * transform.scale.x, player_size.y],
+            })
+            .collect(),
+        balls: shapes
+            .balls
+            .iter()
+            .map(|transform| transform.translation.truncate().to_array())
+            .collect(),
+        blocks,
+        capsules: shapes
+            .capsules
+            .iter()
+            .map(|(transform, material)| CapsuleState {
+                position: transform.translation.truncate().to_array(),
+                color: linear_color(&materials, material),
+            })
+            .collect(),
+    };
+
+    host.send(peer_address, &HostMessage::Snapshot(snapshot));
+}
+
+fn linear_color(
+    materials: &Assets<ColorMaterial>,
+    material: &MeshMaterial2d<ColorMaterial>,
+) -> [f32; 4] {
+    materials.get(&material.0).map_or([1.0; 4], |material| {
+        material.color.to_linear().to_f32_array()
+    })
+}
+
+fn say_goodbye_system(host: Res<Host>) {
+    if let Some(address) = host.peer_address() {
+        host.send(address, &HostMessage::Disconnect);
+    };
+}
@@ -0,0 +1,190 @@
+//! The messages the host and the client exchange. Every message is one UDP datagram encoded with
+//! bincode.
+//!
+//! Blocks do not move, so the host sends where they are once per level in [`LayoutChunk`]s. The
+//! snapshots only say which of them are left. That keeps them small, but nothing bounds them: many
+//! balls or damaged blocks can exceed the [`DATAGRAM_BUDGET`](super::socket::DATAGRAM_BUDGET).
+
+use serde::{Deserialize, Serialize};
+
+use crate::{game_mode::GameMode, player::Player};
+
+/// Bumped whenever a message changes. The host rejects clients with another version.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Asks to join. Resent until the host answers.
+    Hello {
+        version: u32,
+    },
+    Input(RemoteInput),
+    Disconnect,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HostMessage {
+    Welcome,
+    Rejected(RejectReason),
+    Snapshot(Snapshot),
+    Layout(LayoutChunk),
+    Disconnect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RejectReason {
+    VersionMismatch {
+        host: u32,
+    },
+    /// Another client already plays [`Player::Second`].
+    Full,
+}
+
+impl RejectReason {
+    /// Why the client could not join, for the log.
+    pub fn description(&self) -> String {
+        match self {
+            RejectReason::VersionMismatch { host } => {
+                format!("the host uses protocol version {host}, this game {PROTOCOL_VERSION}")
+            }
+            RejectReason::Full => "another player already joined".to_string(),
+        }
+    }
+}
+
+/// The paddle input of the client, sent every frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RemoteInput {
+    /// Counts up with every message, so the host can drop inputs that arrive out of order.
+    pub sequence: u32,
+    /// Horizontal movement between -1 and 1.
+    pub axis: f32,
+    /// How often the client pressed launch so far. A count instead of a flag survives lost
+    /// packets.
+    pub launches: u32,
+    /// The [`LayoutChunk::layout`] the client has all chunks of, `0` for none. The host keeps
+    /// sending the current layout until the client has it.
+    pub layout: u32,
+}
+
+/// Everything the client needs to draw one frame of the host's game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Counts up with every snapshot, so the client can drop snapshots that arrive out of order.
+    pub sequence: u32,
+    pub phase: Phase,
+    pub game_mode: GameMode,
+    pub level_name: String,
+    /// The size of the host's playfield. The client scales its view to fit it.
+    pub field_size: [f32; 2],
+    pub points: u32,
+    pub lives: u32,
+    /// Score and lives of each player in competitive modes.
+    pub per_player: [PlayerState; 2],
+    pub paddles: Vec<PaddleState>,
+    pub balls: Vec<[f32; 2]>,
+    pub blocks: BlockStates,
+    pub capsules: Vec<CapsuleState>,
+}
+
+/// Which blocks of a layout are left.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockStates {
+    /// The layout the blocks belong to, `0` while there is none.
+    pub layout: u32,
+    /// One bit per block of the layout, set while the block is left.
+    pub remaining: Vec<u8>,
+    /// Blocks that took hits and changed their color since the layout was sent.
+    pub damaged: Vec<DamagedBlock>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DamagedBlock {
+    pub index: u16,
+    /// Linear RGBA.
+    pub color: [f32; 4],
+}
+
+/// Part of the blocks of a level, in as many chunks as it takes to fit each into one datagram.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutChunk {
+    /// Counts up with every level the host loads, starting at 1.
+    pub layout: u32,
+    pub block_size: [f32; 2],
+    /// How many blocks the whole layout has.
+    pub block_count: u16,
+    /// The index of the first block of this chunk.
+    pub first: u16,
+    pub blocks: Vec<LayoutBlock>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LayoutBlock {
+    pub position: [f32; 2],
+    /// Linear RGBA.
+    pub color: [f32; 4],
+}
+
+/// What the host is currently showing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /// The host is in a menu.
+    Waiting,
+    Playing,
+    Paused,
+    LevelComplete,
+    CampaignWon,
+    GameOver,
+    RoundOver {
+        winner: Option<Player>,
+    },
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub points: u32,
+    pub lives: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaddleState {
+    pub player: Player,
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+}
+
+/// A block as the client draws it, put together from the layout and [`BlockStates`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlockState {
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    /// Linear RGBA.
+    pub color: [f32; 4],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CapsuleState {
+    pub position: [f32; 2],
+    /// Linear RGBA.
+    pub color: [f32; 4],
+}
+
+impl BlockStates {
+    pub fn is_remaining(&self, index: usize) -> bool {
+        self.remaining
+            .get(index / 8)
+            .is_some_and(|bits| bits & (1 << (index % 8)) != 0)
+    }
+
+    pub fn set_remaining(&mut self, index: usize) {
+        if self.remaining.len() <= index / 8 {
+            self.remaining.resize(index / 8 + 1, 0);
+        };
+        self.remaining[index / 8] |= 1 << (index % 8);
+    }
+
+    /// How many blocks are left.
+    pub fn remaining_count(&self) -> u32 {
+        self.remaining.iter().map(|bits| bits.count_ones()).sum()
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+};
+
+use bevy::prelude::*;
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+/// The largest payload of a UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 65_507;
+/// Larger datagrams may be split into several packets, and losing any of them loses the whole
+/// message. Debug builds warn about messages above it.
+pub const DATAGRAM_BUDGET: usize = 1200;
+
+/// A non-blocking UDP socket that sends and receives whole messages.
+#[derive(Debug)]
+pub struct NetSocket(UdpSocket);
+
+#[derive(Debug, Error)]
+pub enum NetError {
+    #[error("Socket error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Could not encode the message: {0}")]
+    Encode(#[from] bincode::error::EncodeError),
+    #[error("Could not decode the message: {0}")]
+    Decode(#[from] bincode::error::DecodeError),
+    #[error("The message is {0} bytes, more than fits into one datagram")]
+    TooLarge(usize),
+}
+
+impl NetSocket {
+    pub fn bind(address: SocketAddr) -> Result<Self, NetError> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self(socket))
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetError> {
+        Ok(self.0.local_addr()?)
+    }
+
+    pub fn send<T: Serialize>(&self, to: SocketAddr, message: &T) -> Result<(), NetError> {
+        let bytes = bincode::serde::encode_to_vec(message, bincode::config::standard())?;
+        if bytes.len() > MAX_DATAGRAM_SIZE {
+            return Err(NetError::TooLarge(bytes.len()));
+        };
+        if cfg!(debug_assertions) && bytes.len() > DATAGRAM_BUDGET {
+            warn_once!(
+                "Sent a message of {} bytes, more than the budget of {DATAGRAM_BUDGET}",
+                bytes.len()
+            );
+        };
+
+        self.0.send_to(&bytes, to)?;
+        Ok(())
+    }
+
+    /// All messages that arrived since the last call. Malformed datagrams are skipped.
+    pub fn receive<T: DeserializeOwned>(&self) -> Vec<(SocketAddr, T)> {
+        let mut buffer = [0; MAX_DATAGRAM_SIZE];
+        let mut messages = Vec::new();
+
+        loop {
+            let (length, from) = match self.0.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                // Windows reports a closed port of the peer on the next receive. The timeout
+                // takes care of peers that are really gone.
+                Err(error) if error.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(error) => {
+                    warn!("Could not receive from the socket: {error}");
+                    break;
+                }
+            };
+
+            match bincode::serde::decode_from_slice(&buffer[..length], bincode::config::standard())
+            {
+                Ok((message, _)) => messages.push((from, message)),
+                Err(error) => debug!("Ignored a malformed message from {from}: {error}"),
+            };
+        }
+
+        messages
+    }
+}
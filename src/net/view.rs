@@ -0,0 +1,274 @@
+//! Draws the snapshots of the host on the client.
+
+use bevy::{prelude::*, render::camera::ScalingMode};
+
+use crate::{
+    app_state::AppState,
+    ball::BALL_RADIUS,
+    net::{client::LatestSnapshot, protocol::Phase},
+    player::Player,
+    power_up::{CAPSULE_LENGTH, CAPSULE_RADIUS},
+    ui,
+};
+
+#[derive(Debug)]
+pub struct ViewPlugin;
+
+impl Plugin for ViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup)
+            .add_systems(OnEnter(AppState::RemoteGame), view_setup)
+            .add_systems(
+                OnExit(AppState::RemoteGame),
+                (
+                    ui::despawn_screen::<RemoteView>,
+                    ui::despawn_screen::<Shape>,
+                    reset_camera_system,
+                ),
+            )
+            .add_systems(
+                Update,
+                (sync_shapes_system, update_texts_system, fit_camera_system)
+                    .run_if(in_state(AppState::RemoteGame))
+                    .run_if(resource_exists_and_changed::<LatestSnapshot>),
+            );
+    }
+}
+
+#[derive(Debug, Resource)]
+struct ViewAssets {
+    /// A unit square, scaled to the size of paddles and blocks.
+    square: Handle<Mesh>,
+    ball: Handle<Mesh>,
+    capsule: Handle<Mesh>,
+    materials: Vec<([f32; 4], Handle<ColorMaterial>)>,
+}
+
+/// The texts on top of the remote game.
+#[derive(Debug, Component)]
+struct RemoteView;
+
+#[derive(Debug, Component)]
+struct StatusText;
+
+#[derive(Debug, Component)]
+struct InfoText;
+
+/// Something the host drew. Shapes are reused between snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+enum Shape {
+    Paddle,
+    Ball,
+    Block,
+    Capsule,
+}
+
+impl ViewAssets {
+    fn mesh(&self, shape: Shape) -> Handle<Mesh> {
+        match shape {
+            Shape::Paddle | Shape::Block => self.square.clone(),
+            Shape::Ball => self.ball.clone(),
+            Shape::Capsule => self.capsule.clone(),
+        }
+    }
+
+    /// Shares one material between all shapes of the same color.
+    fn material(
+        &mut self,
+        materials: &mut Assets<ColorMaterial>,
+        color: [f32; 4],
+    ) -> Handle<ColorMaterial> {
+        if let Some((_, handle)) = self.materials.iter().find(|(other, _)| *other == color) {
+            return handle.clone();
+        };
+
+        let handle = materials.add(Color::LinearRgba(LinearRgba::from_f32_array(color)));
+        self.materials.push((color, handle.clone()));
+        handle
+    }
+}
+
+fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(ViewAssets {
+        square: meshes.add(Rectangle::new(1.0, 1.0)),
+        ball: meshes.add(Circle::new(BALL_RADIUS)),
+        capsule: meshes.add(Capsule2d::new(CAPSULE_RADIUS, CAPSULE_LENGTH)),
+        materials: Vec::new(),
+    });
+}
+
+fn view_setup(mut commands: Commands) {
+    commands.spawn((
+        RemoteView,
+        ui::screen(),
+        children![(ui::title("Waiting for the host"), StatusText)],
+    ));
+
+    commands.spawn((
+        RemoteView,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            padding: UiRect::all(Val::Px(8.0)),
+            ..Default::default()
+        },
+        children![(ui::label(String::new()), InfoText)],
+    ));
+}
+
+fn sync_shapes_system(
+    mut commands: Commands,
+    snapshot: Res<LatestSnapshot>,
+    mut view_assets: ResMut<ViewAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut shapes: Query<(
+        Entity,
+        &Shape,
+        &mut Transform,
+        &mut MeshMaterial2d<ColorMaterial>,
+    )>,
+) {
+    let mut unused = shapes.iter_mut().collect::<Vec<_>>();
+
+    for (shape, transform, color) in shapes_of(&snapshot) {
+        let material = view_assets.material(&mut materials, color);
+
+        match unused.iter().position(|(_, other, ..)| **other == shape) {
+            Some(index) => {
+                let (_, _, mut old_transform, mut old_material) = unused.swap_remove(index);
+                old_transform.set_if_neq(transform);
+                if old_material.0 != material {
+                    old_material.0 = material;
+                };
+            }
+            None => {
+                commands.spawn((
+                    shape,
+                    Mesh2d(view_assets.mesh(shape)),
+                    MeshMaterial2d(material),
+                    transform,
+                ));
+            }
+        };
+    }
+
+    for (entity, ..) in unused {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Every shape of the snapshot with its transform and color.
+fn shapes_of(latest: &LatestSnapshot) -> Vec<(Shape, Transform, [f32; 4])> {
+    let snapshot = &latest.snapshot;
+    let paddles = snapshot.paddles.iter().map(|paddle| {
+        let color = match paddle.player {
+            Player::First => [0.0, 0.0, 1.0, 1.0],
+            Player::Second => [1.0, 0.0, 0.0, 1.0],
+        };
+        let transform = Transform::from_translation(Vec2::from(paddle.position).extend(0.0))
+            .with_scale(Vec2::from(paddle.size).extend(1.0));
+
+        (Shape::Paddle, transform, color)
+    });
+    let balls = snapshot.balls.iter().map(|position| {
+        let transform = Transform::from_translation(Vec2::from(*position).extend(1.0));
+
+        (Shape::Ball, transform, [1.0; 4])
+    });
+    let blocks = latest.blocks.iter().map(|block| {
+        let transform = Transform::from_translation(Vec2::from(block.position).extend(0.0))
+            .with_scale(Vec2::from(block.size).extend(1.0));
+
+        (Shape::Block, transform, block.color)
+    });
+    let capsules = snapshot.capsules.iter().map(|capsule| {
+        let transform = Transform::from_translation(Vec2::from(capsule.position).extend(0.5))
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+
+        (Shape::Capsule, transform, capsule.color)
+    });
+
+    paddles.chain(balls).chain(blocks).chain(capsules).collect()
+}
+
+fn update_texts_system(
+    snapshot: Res<LatestSnapshot>,
+    mut status_texts: Query<&mut Text, (With<StatusText>, Without<InfoText>)>,
+    mut info_texts: Query<&mut Text, (With<InfoText>, Without<StatusText>)>,
+) {
+    let snapshot = &snapshot.snapshot;
+
+    let status = match &snapshot.phase {
+        Phase::Waiting => "Waiting for the host".to_string(),
+        Phase::Playing => String::new(),
+        Phase::Paused => "PAUSED".to_string(),
+        Phase::LevelComplete => "LEVEL COMPLETE".to_string(),
+        Phase::CampaignWon => "YOU WIN".to_string(),
+        Phase::GameOver => "GAME OVER".to_string(),
+        Phase::RoundOver { winner } => match winner {
+            Some(player) => format!("{} WINS", player.short_name()),
+            None => "DRAW".to_string(),
+        },
+    };
+
+    let mut parts = vec![snapshot.level_name.clone()];
+    if snapshot.game_mode.is_competitive() {
+        for (player, state) in [Player::First, Player::Second]
+            .iter()
+            .zip(&snapshot.per_player)
+        {
+            parts.push(format!(
+                "{}: {} points, {} lives",
+                player.short_name(),
+                state.points,
+                state.lives
+            ));
+        }
+    } else {
+        parts.push(format!("Score: {}", snapshot.points));
+        parts.push(format!("Lives: {}", snapshot.lives));
+    };
+    // Leave the bar empty while the host is in the menu.
+    let info = if snapshot.phase == Phase::Waiting {
+        String::new()
+    } else {
+        parts.join("    ")
+    };
+
+    for mut text in status_texts.iter_mut() {
+        text.set_if_neq(Text(status.clone()));
+    }
+    for mut text in info_texts.iter_mut() {
+        text.set_if_neq(Text(info.clone()));
+    }
+}
+
+/// Shows the whole field of the host, even if this window has another size.
+fn fit_camera_system(
+    snapshot: Res<LatestSnapshot>,
+    mut cameras: Query<&mut Projection, With<Camera2d>>,
+) {
+    let [width, height] = snapshot.snapshot.field_size;
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    };
+
+    for mut projection in cameras.iter_mut() {
+        if let Projection::Orthographic(orthographic) = &mut *projection {
+            orthographic.scaling_mode = ScalingMode::AutoMin {
+                min_width: width,
+                min_height: height,
+            };
+        };
+    }
+}
+
+fn reset_camera_system(mut cameras: Query<&mut Projection, With<Camera2d>>) {
+    for mut projection in cameras.iter_mut() {
+        if let Projection::Orthographic(orthographic) = &mut *projection {
+            orthographic.scaling_mode = ScalingMode::WindowSize;
+        };
+    }
+}
@@ -0,0 +1,411 @@
+use avian2d::prelude::{Physics, PhysicsTime};
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    RestartLevel, StopGame,
+    app_state::{AppState, GameState, PauseMenuState},
+    input::{Action, InputBindings},
+    pause_menu::controls::{ControlsPlugin, Rebinding},
+    player::{
+        Player,
+        mouse::{MouseControl, MouseMode},
+    },
+    ui,
+};
+
+mod controls;
+
+const VOLUME_STEP: f32 = 0.1;
+const SENSITIVITY_STEP: f32 = 0.25;
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ControlsPlugin);
+
+        app.add_systems(
+            OnEnter(GameState::Paused),
+            (pause_physics_system, pause_overlay_setup),
+        )
+        .add_systems(
+            OnExit(GameState::Paused),
+            (unpause_physics_system, ui::despawn_screen::<PauseOverlay>),
+        )
+        .add_systems(OnEnter(PauseMenuState::Main), pause_menu_setup)
+        .add_systems(
+            OnExit(PauseMenuState::Main),
+            ui::despawn_screen::<PauseMenu>,
+        )
+        .add_systems(OnEnter(PauseMenuState::Settings), settings_menu_setup)
+        .add_systems(
+            OnExit(PauseMenuState::Settings),
+            ui::despawn_screen::<SettingsMenu>,
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_pause_on_esc_system,
+                (
+                    resume_button_pressed_system,
+                    restart_button_pressed_system,
+                    settings_button_pressed_system,
+                    quit_button_pressed_system,
+                )
+                    .run_if(in_state(PauseMenuState::Main)),
+                (
+                    volume_button_pressed_system,
+                    back_button_pressed_system,
+                    controls_button_pressed_system,
+                    update_volume_text_system.run_if(resource_changed::<GlobalVolume>),
+                    mouse_mode_button_pressed_system,
+                    grab_cursor_button_pressed_system,
+                    sensitivity_button_pressed_system,
+                    update_mouse_text_system.run_if(resource_changed::<MouseControl>),
+                )
+                    .run_if(in_state(PauseMenuState::Settings)),
+            )
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+/// Dims the playfield while paused and holds the current menu page.
+#[derive(Debug, Component)]
+struct PauseOverlay;
+
+#[derive(Debug, Component)]
+struct PauseMenu;
+
+#[derive(Debug, Component)]
+struct SettingsMenu;
+
+#[derive(Debug, Component)]
+struct ResumeButton;
+
+#[derive(Debug, Component)]
+struct RestartButton;
+
+#[derive(Debug, Component)]
+struct SettingsButton;
+
+#[derive(Debug, Component)]
+struct QuitButton;
+
+#[derive(Debug, Component)]
+struct VolumeButton(f32);
+
+#[derive(Debug, Component)]
+struct VolumeText;
+
+#[derive(Debug, Component)]
+struct MouseText;
+
+/// Cycles between keyboard only, absolute and relative mouse control.
+#[derive(Debug, Component)]
+struct MouseModeButton;
+
+#[derive(Debug, Component)]
+struct GrabCursorButton;
+
+#[derive(Debug, Component)]
+struct SensitivityButton(f32);
+
+#[derive(Debug, Component)]
+struct ControlsButton;
+
+#[derive(Debug, Component)]
+struct BackButton;
+
+fn pause_physics_system(mut time: ResMut<Time<Physics>>) {
+    time.pause();
+    info!("Paused");
+}
+
+fn unpause_physics_system(mut time: ResMut<Time<Physics>>) {
+    time.unpause();
+    info!("Resumed");
+}
+
+fn pause_overlay_setup(mut commands: Commands) {
+    commands.spawn((
+        PauseOverlay,
+        ui::screen(),
+        BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 0.6)),
+        GlobalZIndex(1),
+    ));
+}
+
+fn pause_menu_setup(mut commands: Commands, overlays: Query<Entity, With<PauseOverlay>>) {
+    for overlay in overlays {
+        commands.spawn((
+            PauseMenu,
+            ChildOf(overlay),
+            ui::button_column(),
+            children![
+                ui::title("PAUSED"),
+                (ui::button("Resume"), ResumeButton),
+                (ui::button("Restart Level"), RestartButton),
+                (ui::button("Settings"), SettingsButton),
+                (ui::button("Quit to Menu"), QuitButton),
+            ],
+        ));
+    }
+}
+
+fn settings_menu_setup(
+    mut commands: Commands,
+    global_volume: Res<GlobalVolume>,
+    mouse_control: Res<MouseControl>,
+    overlays: Query<Entity, With<PauseOverlay>>,
+) {
+    for overlay in overlays {
+        commands.spawn((
+            SettingsMenu,
+            ChildOf(overlay),
+            ui::button_column(),
+            children![
+                ui::title("SETTINGS"),
+                (ui::label(volume_text(&global_volume)), VolumeText),
+                (
+                    Node {
+                        column_gap: Val::Px(12.0),
+                        ..Default::default()
+                    },
+                    children![
+                        (ui::button("Volume -"), VolumeButton(-VOLUME_STEP)),
+                        (ui::button("Volume +"), VolumeButton(VOLUME_STEP)),
+                    ]
+                ),
+                (ui::label(mouse_text(&mouse_control)), MouseText),
+                (
+                    Node {
+                        column_gap: Val::Px(12.0),
+                        ..Default::default()
+                    },
+                    children![
+                        (ui::button("Mouse Mode"), MouseModeButton),
+                        (ui::button("Grab Cursor"), GrabCursorButton),
+                        (
+                            ui::button("Sensitivity -"),
+                            SensitivityButton(-SENSITIVITY_STEP)
+                        ),
+                        (
+                            ui::button("Sensitivity +"),
+                            SensitivityButton(SENSITIVITY_STEP)
+                        ),
+                    ]
+                ),
+                (
+                    Node {
+                        column_gap: Val::Px(12.0),
+                        ..Default::default()
+                    },
+                    children![
+                        (ui::button("Controls"), ControlsButton),
+                        (ui::button("Back"), BackButton),
+                    ]
+                ),
+            ],
+        ));
+    }
+}
+
+fn toggle_pause_on_esc_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    gamepads: Query<&Gamepad>,
+    game_state: Res<State<GameState>>,
+    pause_menu_state: Option<Res<State<PauseMenuState>>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_pause_menu_state: ResMut<NextState<PauseMenuState>>,
+) {
+    // Every key, including the pause keys, can be bound while rebinding.
+    if rebinding.0.is_some() {
+        return;
+    };
+
+    let key_pressed = [Player::First, Player::Second]
+        .iter()
+        .any(|player| bindings.just_pressed(&keys, player, Action::Pause));
+    let start_pressed = gamepads
+        .iter()
+        .any(|gamepad| gamepad.just_pressed(GamepadButton::Start));
+    if !key_pressed && !start_pressed {
+        return;
+    };
+
+    match (
+        game_state.get(),
+        pause_menu_state.as_deref().map(State::get),
+    ) {
+        (GameState::Running, _) => next_game_state.set(GameState::Paused),
+        (GameState::Paused, Some(PauseMenuState::Settings)) => {
+            next_pause_menu_state.set(PauseMenuState::Main)
+        }
+        (GameState::Paused, Some(PauseMenuState::Controls)) => {
+            next_pause_menu_state.set(PauseMenuState::Settings)
+        }
+        (GameState::Paused, _) => next_game_state.set(GameState::Running),
+    };
+}
+
+fn resume_button_pressed_system(
+    mut next_game_state: ResMut<NextState<GameState>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<ResumeButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            next_game_state.set(GameState::Running);
+        };
+    }
+}
+
+fn restart_button_pressed_system(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<RestartButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(RestartLevel);
+        };
+    }
+}
+
+fn settings_button_pressed_system(
+    mut next_pause_menu_state: ResMut<NextState<PauseMenuState>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<SettingsButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            next_pause_menu_state.set(PauseMenuState::Settings);
+        };
+    }
+}
+
+fn quit_button_pressed_system(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<QuitButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(StopGame);
+        };
+    }
+}
+
+fn volume_button_pressed_system(
+    mut global_volume: ResMut<GlobalVolume>,
+    buttons: Query<(&Interaction, &VolumeButton), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, volume_button) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            let linear = (global_volume.volume.to_linear() + volume_button.0).clamp(0.0, 1.0);
+            global_volume.volume = Volume::Linear(linear);
+        };
+    }
+}
+
+fn mouse_mode_button_pressed_system(
+    mut mouse_control: ResMut<MouseControl>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<MouseModeButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        };
+
+        (mouse_control.enabled, mouse_control.mode) =
+            match (mouse_control.enabled, mouse_control.mode) {
+                (false, _) => (true, MouseMode::Absolute),
+                (true, MouseMode::Absolute) => (true, MouseMode::Relative),
+                (true, MouseMode::Relative) => (false, MouseMode::Absolute),
+            };
+    }
+}
+
+fn grab_cursor_button_pressed_system(
+    mut mouse_control: ResMut<MouseControl>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<GrabCursorButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            mouse_control.grab_cursor = !mouse_control.grab_cursor;
+        };
+    }
+}
+
+fn sensitivity_button_pressed_system(
+    mut mouse_control: ResMut<MouseControl>,
+    buttons: Query<(&Interaction, &SensitivityButton), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, sensitivity_button) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            mouse_control.sensitivity =
+                (mouse_control.sensitivity + sensitivity_button.0).clamp(0.25, 4.0);
+        };
+    }
+}
+
+fn controls_button_pressed_system(
+    mut next_pause_menu_state: ResMut<NextState<PauseMenuState>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<ControlsButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            next_pause_menu_state.set(PauseMenuState::Controls);
+        };
+    }
+}
+
+fn back_button_pressed_system(
+    mut next_pause_menu_state: ResMut<NextState<PauseMenuState>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<BackButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            next_pause_menu_state.set(PauseMenuState::Main);
+        };
+    }
+}
+
+fn update_volume_text_system(
+    global_volume: Res<GlobalVolume>,
+    mut texts: Query<&mut Text, With<VolumeText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = volume_text(&global_volume);
+    }
+}
+
+fn update_mouse_text_system(
+    mouse_control: Res<MouseControl>,
+    mut texts: Query<&mut Text, With<MouseText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = mouse_text(&mouse_control);
+    }
+}
+
+fn mouse_text(mouse_control: &MouseControl) -> String {
+    if !mouse_control.enabled {
+        return "Mouse: Off".to_string();
+    };
+
+    let mode = match mouse_control.mode {
+        MouseMode::Absolute => "Absolute",
+        MouseMode::Relative => "Relative",
+    };
+    let grab = if mouse_control.grab_cursor {
+        ", cursor grabbed"
+    } else {
+        ""
+    };
+
+    format!("Mouse: {mode} x{:.2}{grab}", mouse_control.sensitivity)
+}
+
+fn volume_text(global_volume: &GlobalVolume) -> String {
+    format!("Volume: {:.0}%", global_volume.volume.to_linear() * 100.0)
+}
@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+
+use crate::{
+    app_state::{AppState, PauseMenuState},
+    input::{Action, InputBindings, key_name},
+    pause_menu::{PauseOverlay, toggle_pause_on_esc_system},
+    player::Player,
+    ui,
+};
+
+/// The pause menu page to rebind the keys of both players.
+#[derive(Debug)]
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>();
+
+        app.add_systems(OnEnter(PauseMenuState::Controls), controls_menu_setup)
+            .add_systems(
+                OnExit(PauseMenuState::Controls),
+                (ui::despawn_screen::<ControlsMenu>, stop_rebinding_system),
+            )
+            .add_systems(
+                Update,
+                (
+                    rebind_button_pressed_system,
+                    capture_key_system,
+                    back_button_pressed_system,
+                    update_rebind_buttons_system.run_if(
+                        resource_changed::<InputBindings>.or(resource_changed::<Rebinding>),
+                    ),
+                )
+                    .chain()
+                    // The key that closes the page must not also be captured as a new binding.
+                    .after(toggle_pause_on_esc_system)
+                    .run_if(in_state(PauseMenuState::Controls))
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// The action that waits for a key press to bind it.
+#[derive(Debug, Default, Resource)]
+pub struct Rebinding(pub Option<(Player, Action)>);
+
+#[derive(Debug, Component)]
+struct ControlsMenu;
+
+#[derive(Debug, Component)]
+struct RebindButton(Player, Action);
+
+/// Tells the player what to press and which bindings were swapped.
+#[derive(Debug, Component)]
+struct StatusText;
+
+#[derive(Debug, Component)]
+struct BackButton;
+
+fn controls_menu_setup(
+    mut commands: Commands,
+    bindings: Res<InputBindings>,
+    overlays: Query<Entity, With<PauseOverlay>>,
+) {
+    for overlay in overlays {
+        let menu = commands
+            .spawn((
+                ControlsMenu,
+                ChildOf(overlay),
+                ui::button_column(),
+                children![
+                    ui::title("CONTROLS"),
+                    (
+                        Node {
+                            column_gap: Val::Px(12.0),
+                            ..Default::default()
+                        },
+                        children![
+                            (ui::label(""), action_column()),
+                            (ui::label("P1"), header_column()),
+                            (ui::label("P2"), header_column()),
+                        ]
+                    ),
+                ],
+            ))
+            .id();
+
+        for action in Action::ALL {
+            let row = commands
+                .spawn((
+                    ChildOf(menu),
+                    Node {
+                        column_gap: Val::Px(12.0),
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    children![(ui::label(action.to_string()), action_column())],
+                ))
+                .id();
+
+            for player in [Player::First, Player::Second] {
+                commands.spawn((
+                    ChildOf(row),
+                    ui::button(key_name(bindings.key(&player, action))),
+                    RebindButton(player, action),
+                ));
+            }
+        }
+
+        commands.spawn((
+            ChildOf(menu),
+            ui::label("Click an action to rebind it"),
+            StatusText,
+        ));
+        commands.spawn((ChildOf(menu), ui::button("Back"), BackButton));
+    }
+}
+
+/// Lines the action names up with the rebind buttons next to them.
+fn action_column() -> Node {
+    Node {
+        width: Val::Px(180.0),
+        ..Default::default()
+    }
+}
+
+/// As wide as a button, so the player names sit above their buttons.
+fn header_column() -> impl Bundle {
+    (
+        Node {
+            width: Val::Px(220.0),
+            ..Default::default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+    )
+}
+
+fn rebind_button_pressed_system(
+    mut rebinding: ResMut<Rebinding>,
+    buttons: Query<(&Interaction, &RebindButton), (Changed<Interaction>, With<Button>)>,
+    mut status_texts: Query<&mut Text, With<StatusText>>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        };
+
+        rebinding.0 = Some((button.0.clone(), button.1));
+        for mut text in status_texts.iter_mut() {
+            text.0 = format!("Press a key for {} {}", button.0.short_name(), button.1);
+        }
+    }
+}
+
+fn capture_key_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+    mut status_texts: Query<&mut Text, With<StatusText>>,
+) {
+    let Some((player, action)) = rebinding.0.clone() else {
+        return;
+    };
+    let Some(&key) = keys.get_just_pressed().next() else {
+        return;
+    };
+
+    let status = match bindings.rebind(&player, action, key) {
+        Some((other_player, other_action)) => format!(
+            "{} was used by {} {}, they swapped keys",
+            key_name(key),
+            other_player.short_name(),
+            other_action
+        ),
+        None => format!(
+            "{} {} is now {}",
+            player.short_name(),
+            action,
+            key_name(key)
+        ),
+    };
+    rebinding.0 = None;
+
+    if let Err(error) = bindings.save() {
+        warn!("{error}");
+    };
+
+    for mut text in status_texts.iter_mut() {
+        text.0 = status.clone();
+    }
+}
+
+fn back_button_pressed_system(
+    mut next_pause_menu_state: ResMut<NextState<PauseMenuState>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<BackButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            next_pause_menu_state.set(PauseMenuState::Settings);
+        };
+    }
+}
+
+fn update_rebind_buttons_system(
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    buttons: Query<(&RebindButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in buttons.iter() {
+        let waiting = rebinding
+            .0
+            .as_ref()
+            .is_some_and(|(player, action)| *player == button.0 && *action == button.1);
+        let label = if waiting {
+            "...".to_string()
+        } else {
+            key_name(bindings.key(&button.0, button.1))
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.0 = label.clone();
+            };
+        }
+    }
+}
+
+fn stop_rebinding_system(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
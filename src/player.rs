@@ -1,10 +1,19 @@
 use std::ops::Deref;
 
-use avian2d::prelude::{Collider, Friction, LockedAxes, Restitution, RigidBody};
-use bevy::{prelude::*, window::PrimaryWindow};
+use avian2d::prelude::{
+    Collider, CollisionEventsEnabled, Friction, LockedAxes, Restitution, RigidBody,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::app_state::AppState;
+use crate::{
+    app_state::AppState, game_mode::GameMode, input::PlayerInputs, player::mouse::MousePlugin,
+    simulation::SimulationSet, world::Playfield,
+};
 
+pub mod mouse;
+
+/// How fast a paddle moves at full deflection, in pixels per second.
 const SPEED: f32 = 512.0;
 
 const PADDLE_HEIGHT: f32 = 10.0;
@@ -14,16 +23,25 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
+        app.add_plugins(MousePlugin);
+
         app.add_observer(spawn_paddle_observer);
 
         app.add_systems(Startup, setup)
-            .add_systems(OnEnter(AppState::InGame), player_spawn_system)
-            .add_systems(OnEnter(AppState::MainMenu), player_despawn_system)
             .add_systems(
-                Update,
-                (player_movement_system, handle_border_collision_system)
+                OnEnter(AppState::InGame),
+                player_spawn_system.in_set(SpawnPlayers),
+            )
+            .add_systems(OnExit(AppState::InGame), player_despawn_system)
+            .add_systems(
+                FixedUpdate,
+                (
+                    player_movement_system,
+                    handle_border_collision_system,
+                    track_paddle_velocity_system,
+                )
                     .chain()
-                    .run_if(in_state(AppState::InGame)),
+                    .in_set(SimulationSet::Paddles),
             );
     }
 }
@@ -31,18 +49,72 @@ impl Plugin for PlayerPlugin {
 #[derive(Debug, Resource)]
 struct PlayerMeshResource(Handle<Mesh>);
 
+/// The unscaled size of a paddle. Power-ups change the width through the paddle's scale.
 #[derive(Debug, Resource)]
-struct PlayerSize(Vec2);
+pub struct PlayerSize(Vec2);
 
-#[derive(Debug, Clone, PartialEq, Eq, Component)]
+#[derive(Debug, Clone, PartialEq, Eq, Component, Serialize, Deserialize)]
 pub enum Player {
     First,
     Second,
 }
 
+/// One value for each player, e.g. their score in [`GameMode::Versus`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerPlayer<T> {
+    pub first: T,
+    pub second: T,
+}
+
+/// Spawns the paddles when entering the game. Systems that need the paddles run after it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct SpawnPlayers;
+
+/// Horizontal speed of a paddle, measured from how far it moved during the last simulation step.
+#[derive(Debug, Default, Component)]
+pub struct PaddleVelocity {
+    pub x: f32,
+    last_x: f32,
+}
+
 #[derive(Debug, Event)]
 struct SpawnPlayer(Player);
 
+impl Player {
+    /// `P1` or `P2`, for labels in the UI.
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Player::First => "P1",
+            Player::Second => "P2",
+        }
+    }
+}
+
+impl<T: Clone> PerPlayer<T> {
+    pub fn splat(value: T) -> Self {
+        Self {
+            first: value.clone(),
+            second: value,
+        }
+    }
+}
+
+impl<T> PerPlayer<T> {
+    pub fn get(&self, player: &Player) -> &T {
+        match player {
+            Player::First => &self.first,
+            Player::Second => &self.second,
+        }
+    }
+
+    pub fn get_mut(&mut self, player: &Player) -> &mut T {
+        match player {
+            Player::First => &mut self.first,
+            Player::Second => &mut self.second,
+        }
+    }
+}
+
 impl Deref for PlayerSize {
     type Target = Vec2;
 
@@ -51,27 +123,20 @@ impl Deref for PlayerSize {
     }
 }
 
-fn setup(
-    mut commands: Commands,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-) -> Result {
-    let size = windows.single()?.size();
-
-    let paddle_size = get_paddle_size(&size);
+fn setup(mut commands: Commands, playfield: Res<Playfield>, mut meshes: ResMut<Assets<Mesh>>) {
+    let paddle_size = get_paddle_size(&playfield.size);
     commands.insert_resource(PlayerSize(paddle_size));
 
     let player_mesh = meshes.add(Rectangle::from_size(paddle_size));
     commands.insert_resource(PlayerMeshResource(player_mesh));
-
-    Ok(())
 }
 
-fn player_spawn_system(mut commands: Commands) {
-    commands.trigger(SpawnPlayer(Player::First));
-    commands.trigger(SpawnPlayer(Player::Second));
+fn player_spawn_system(mut commands: Commands, game_mode: Res<GameMode>) {
+    for player in game_mode.players() {
+        commands.trigger(SpawnPlayer(player.clone()));
+    }
 
-    info!("Spawned first player");
+    info!("Spawned {} players", game_mode.players().len());
 }
 
 fn player_despawn_system(mut commands: Commands, players: Query<Entity, With<Player>>) {
@@ -85,8 +150,9 @@ fn player_despawn_system(mut commands: Commands, players: Query<Entity, With<Pla
 fn spawn_paddle_observer(
     trigger: Trigger<SpawnPlayer>,
     player_size: Res<PlayerSize>,
+    game_mode: Res<GameMode>,
     mut commands: Commands,
-    windows: Query<&Window, With<PrimaryWindow>>,
+    playfield: Res<Playfield>,
     player_mesh: Res<PlayerMeshResource>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     players: Query<&Player>,
@@ -97,14 +163,23 @@ fn spawn_paddle_observer(
         };
     }
 
-    let size = windows.single()?.size();
-    let half_size = size / 2.0;
+    let size = playfield.size;
+    let half_size = playfield.half_size();
     let paddle_center = (-half_size.y / 8.0) * 7.0;
 
     let material = materials.add(get_paddle_color(&trigger.0));
-    let transform = match &trigger.0 {
-        Player::First => Transform::from_xyz(0.0, paddle_center + PADDLE_HEIGHT * 0.75, 0.0),
-        Player::Second => Transform::from_xyz(0.0, paddle_center - PADDLE_HEIGHT * 0.75, 0.0),
+    let transform = match (*game_mode, &trigger.0) {
+        (GameMode::Solo, _) => Transform::from_xyz(0.0, paddle_center, 0.0),
+        (GameMode::Coop, Player::First) => {
+            Transform::from_xyz(0.0, paddle_center + PADDLE_HEIGHT * 0.75, 0.0)
+        }
+        (GameMode::Coop, Player::Second) => {
+            Transform::from_xyz(0.0, paddle_center - PADDLE_HEIGHT * 0.75, 0.0)
+        }
+        (GameMode::Versus, Player::First) => Transform::from_xyz(-size.x / 4.0, paddle_center, 0.0),
+        (GameMode::Versus, Player::Second) => Transform::from_xyz(size.x / 4.0, paddle_center, 0.0),
+        (GameMode::SplitField, Player::First) => Transform::from_xyz(0.0, paddle_center, 0.0),
+        (GameMode::SplitField, Player::Second) => Transform::from_xyz(0.0, -paddle_center, 0.0),
     };
 
     commands.spawn((
@@ -112,8 +187,13 @@ fn spawn_paddle_observer(
         Mesh2d(player_mesh.0.clone()),
         MeshMaterial2d(material),
         transform,
+        PaddleVelocity {
+            x: 0.0,
+            last_x: transform.translation.x,
+        },
         RigidBody::Kinematic,
         Collider::rectangle(player_size.x, player_size.y),
+        CollisionEventsEnabled,
         LockedAxes::new().lock_rotation().lock_translation_y(),
         Restitution::new(1.0),
         Friction::new(0.0),
@@ -124,35 +204,47 @@ fn spawn_paddle_observer(
 
 fn player_movement_system(
     time: Res<Time>,
-    keys: Res<ButtonInput<KeyCode>>,
+    inputs: Res<PlayerInputs>,
     mut players: Query<(&Player, &mut Transform)>,
 ) {
     for (player, mut transform) in players.iter_mut() {
-        let (left, right) = match player {
-            Player::First => (KeyCode::KeyA, KeyCode::KeyD),
-            Player::Second => (KeyCode::ArrowLeft, KeyCode::ArrowRight),
-        };
-
-        let axis = i8::from(keys.pressed(right)) - i8::from(keys.pressed(left));
-        if axis != 0 {
-            transform.translation.x += axis as f32 * SPEED * time.delta_secs();
+        let input = inputs.0.get(player);
+        if let Some(target) = input.target {
+            transform.translation.x = target;
+        } else if input.axis != 0.0 {
+            transform.translation.x += input.axis * SPEED * time.delta_secs();
         };
     }
 }
 
 fn handle_border_collision_system(
     player_size: Res<PlayerSize>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    players: Query<&mut Transform, With<Player>>,
-) -> Result {
-    let win_width = windows.single()?.width();
-    let half_limit = win_width * 0.5 - player_size.x * 0.5;
-
-    for mut transform in players {
-        transform.translation.x = transform.translation.x.clamp(-half_limit, half_limit);
+    playfield: Res<Playfield>,
+    game_mode: Res<GameMode>,
+    players: Query<(&Player, &mut Transform)>,
+) {
+    for (player, mut transform) in players {
+        let (min, max) = game_mode.paddle_range(player, playfield.half_size().x);
+        let half_width = player_size.x * transform.scale.x * 0.5;
+        transform.translation.x = transform
+            .translation
+            .x
+            .clamp(min + half_width, max - half_width);
     }
+}
 
-    Ok(())
+fn track_paddle_velocity_system(
+    time: Res<Time>,
+    players: Query<(&mut PaddleVelocity, &Transform), With<Player>>,
+) {
+    if time.delta_secs() == 0.0 {
+        return;
+    };
+
+    for (mut velocity, transform) in players {
+        velocity.x = (transform.translation.x - velocity.last_x) / time.delta_secs();
+        velocity.last_x = transform.translation.x;
+    }
 }
 
 fn get_paddle_color(player: &Player) -> Color {
@@ -162,6 +254,6 @@ fn get_paddle_color(player: &Player) -> Color {
     }
 }
 
-fn get_paddle_size(window_size: &Vec2) -> Vec2 {
-    Vec2::new(window_size.x / 8.0, PADDLE_HEIGHT)
+fn get_paddle_size(field_size: &Vec2) -> Vec2 {
+    Vec2::new(field_size.x / 8.0, PADDLE_HEIGHT)
 }
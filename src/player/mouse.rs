@@ -0,0 +1,117 @@
+use bevy::{
+    input::mouse::AccumulatedMouseMotion,
+    prelude::*,
+    window::{CursorGrabMode, PrimaryWindow},
+};
+
+use crate::{
+    app_state::GameState,
+    input::{PlayerInputs, ReadInput},
+    player::Player,
+};
+
+/// Lets [`Player::First`] move their paddle with the mouse.
+#[derive(Debug)]
+pub struct MousePlugin;
+
+impl Plugin for MousePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MouseControl>();
+
+        app.add_systems(OnEnter(GameState::Running), grab_cursor_system)
+            .add_systems(OnExit(GameState::Running), release_cursor_system)
+            .add_systems(RunFixedMainLoop, mouse_movement_system.in_set(ReadInput));
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct MouseControl {
+    pub enabled: bool,
+    /// Hides the cursor and keeps it inside the window while playing.
+    pub grab_cursor: bool,
+    pub sensitivity: f32,
+    pub mode: MouseMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseMode {
+    /// The paddle sits under the cursor. `sensitivity` scales the distance from the center.
+    Absolute,
+    /// The paddle moves by how far the mouse moved, scaled by `sensitivity`.
+    Relative,
+}
+
+impl Default for MouseControl {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            grab_cursor: false,
+            sensitivity: 1.0,
+            mode: MouseMode::Absolute,
+        }
+    }
+}
+
+fn grab_cursor_system(
+    mouse_control: Res<MouseControl>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !mouse_control.enabled || !mouse_control.grab_cursor {
+        return;
+    };
+
+    for mut window in windows.iter_mut() {
+        // A locked cursor does not move, so absolute mode only confines it to the window.
+        window.cursor_options.grab_mode = match mouse_control.mode {
+            MouseMode::Absolute => CursorGrabMode::Confined,
+            MouseMode::Relative => CursorGrabMode::Locked,
+        };
+        window.cursor_options.visible = false;
+    }
+}
+
+fn release_cursor_system(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in windows.iter_mut() {
+        window.cursor_options.grab_mode = CursorGrabMode::None;
+        window.cursor_options.visible = true;
+    }
+}
+
+fn mouse_movement_system(
+    mouse_control: Res<MouseControl>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    players: Query<(&Player, &Transform)>,
+    mut inputs: ResMut<PlayerInputs>,
+) {
+    // Leave the paddle to the keyboard while the mouse rests.
+    if !mouse_control.enabled || mouse_motion.delta == Vec2::ZERO {
+        return;
+    };
+
+    let Some((_, transform)) = players.iter().find(|(player, _)| **player == Player::First) else {
+        return;
+    };
+    let input = &mut inputs.0.first;
+
+    match mouse_control.mode {
+        MouseMode::Absolute => {
+            let Some(cursor) = windows.iter().find_map(Window::cursor_position) else {
+                return;
+            };
+            let Some(world_position) = cameras.iter().find_map(|(camera, camera_transform)| {
+                camera.viewport_to_world_2d(camera_transform, cursor).ok()
+            }) else {
+                return;
+            };
+
+            input.target = Some(world_position.x * mouse_control.sensitivity);
+        }
+        MouseMode::Relative => {
+            // Frames without a simulation step keep adding to the target that is still pending.
+            let start = input.target.unwrap_or(transform.translation.x);
+            input.target = Some(start + mouse_motion.delta.x * mouse_control.sensitivity);
+        }
+    };
+}
@@ -0,0 +1,408 @@
+//! Power-ups drop from broken blocks as falling capsules. A paddle that catches a capsule gets the
+//! power-up, which stays active until its duration runs out.
+//!
+//! New power-ups implement [`PowerUp`] and are added with [`RegisterPowerUp::register_power_up`].
+
+use std::{sync::Arc, time::Duration};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    app_state::AppState,
+    game_mode::GameMode,
+    player::{Player, PlayerSize},
+    power_up::effects::EffectsPlugin,
+    simulation::{GameRng, SimulationSet},
+    world::{
+        Playfield,
+        block::BlockBreakEvent,
+        level::{CurrentLevel, Level},
+    },
+};
+
+pub mod effects;
+
+pub const CAPSULE_RADIUS: f32 = 8.0;
+pub const CAPSULE_LENGTH: f32 = 24.0;
+const CAPSULE_FALL_SPEED: f32 = 180.0;
+
+#[derive(Debug)]
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerUps>()
+            .register_required_components::<Player, ActivePowerUps>();
+
+        app.add_plugins(EffectsPlugin);
+
+        app.add_observer(drop_power_up_observer)
+            .add_observer(log_collected_observer)
+            .add_observer(log_expired_observer);
+
+        app.add_systems(Startup, setup)
+            .add_systems(Update, check_level_power_ups_system)
+            .add_systems(OnExit(AppState::InGame), despawn_capsules_system)
+            .add_systems(
+                FixedUpdate,
+                (fall_system, catch_capsule_system, expire_power_ups_system)
+                    .chain()
+                    .in_set(SimulationSet::PowerUps),
+            );
+    }
+}
+
+/// A power-up that a paddle can catch.
+pub trait PowerUp: Send + Sync + 'static {
+    /// Unique name. Level files use it to choose which power-ups can drop.
+    fn name(&self) -> &'static str;
+
+    /// The color of the falling capsule.
+    fn color(&self) -> Color;
+
+    /// How long the power-up stays active. `None` for power-ups that only take effect once.
+    fn duration(&self) -> Option<Duration>;
+
+    /// Power-ups of the same group replace each other instead of stacking.
+    fn group(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn apply(&self, world: &mut World, paddle: Entity);
+
+    /// Undoes [`PowerUp::apply`] once the duration ran out.
+    fn expire(&self, _world: &mut World, _paddle: Entity) {}
+}
+
+pub trait RegisterPowerUp {
+    fn register_power_up(&mut self, power_up: impl PowerUp) -> &mut Self;
+}
+
+/// Every registered power-up.
+#[derive(Default, Resource)]
+pub struct PowerUps(Vec<Arc<dyn PowerUp>>);
+
+/// Index into [`PowerUps`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerUpId(usize);
+
+/// The power-ups with a duration that are active on a paddle.
+#[derive(Debug, Default, Component)]
+pub struct ActivePowerUps(pub Vec<ActivePowerUp>);
+
+#[derive(Debug)]
+pub struct ActivePowerUp {
+    pub id: PowerUpId,
+    pub timer: Timer,
+}
+
+/// A falling power-up that a paddle can catch.
+#[derive(Debug, Component)]
+pub struct Capsule {
+    id: PowerUpId,
+    /// `-1.0` if the capsule falls down, `1.0` if it falls up towards a paddle at the top.
+    direction: f32,
+}
+
+/// Shared by every capsule. One material per registered power-up, indexed by [`PowerUpId`].
+#[derive(Debug, Resource)]
+struct CapsuleAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<Handle<ColorMaterial>>,
+}
+
+/// Triggered when a paddle caught a power-up.
+#[derive(Debug, Event)]
+pub struct PowerUpCollected {
+    pub player: Player,
+    pub name: &'static str,
+}
+
+/// Triggered when a power-up ran out.
+#[derive(Debug, Event)]
+pub struct PowerUpExpired {
+    pub player: Player,
+    pub name: &'static str,
+}
+
+impl RegisterPowerUp for App {
+    fn register_power_up(&mut self, power_up: impl PowerUp) -> &mut Self {
+        let mut power_ups = self.world_mut().get_resource_or_init::<PowerUps>();
+        if power_ups.find(power_up.name()).is_some() {
+            warn!("Power-up {} is already registered", power_up.name());
+            return self;
+        };
+
+        power_ups.0.push(Arc::new(power_up));
+        self
+    }
+}
+
+impl PowerUps {
+    pub fn get(&self, id: PowerUpId) -> Option<&Arc<dyn PowerUp>> {
+        self.0.get(id.0)
+    }
+
+    pub fn find(&self, name: &str) -> Option<PowerUpId> {
+        self.0
+            .iter()
+            .position(|power_up| power_up.name() == name)
+            .map(PowerUpId)
+    }
+
+    /// The power-ups that can drop in `level`.
+    fn droppable(&self, level: &Level) -> Vec<PowerUpId> {
+        match &level.power_ups {
+            Some(names) => names.iter().filter_map(|name| self.find(name)).collect(),
+            None => (0..self.0.len()).map(PowerUpId).collect(),
+        }
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    power_ups: Res<PowerUps>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(CapsuleAssets {
+        mesh: meshes.add(Capsule2d::new(CAPSULE_RADIUS, CAPSULE_LENGTH)),
+        materials: power_ups
+            .0
+            .iter()
+            .map(|power_up| materials.add(power_up.color()))
+            .collect(),
+    });
+}
+
+/// Levels name their power-ups, so a typo would silently keep one from dropping.
+fn check_level_power_ups_system(
+    mut level_events: EventReader<AssetEvent<Level>>,
+    power_ups: Res<PowerUps>,
+    levels: Res<Assets<Level>>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in level_events.read() {
+        let AssetEvent::LoadedWithDependencies { id } = event else {
+            continue;
+        };
+        let Some(names) = levels.get(*id).and_then(|level| level.power_ups.as_ref()) else {
+            continue;
+        };
+
+        for name in names.iter().filter(|name| power_ups.find(name).is_none()) {
+            let path = asset_server
+                .get_path(*id)
+                .map(|path| path.to_string())
+                .unwrap_or_default();
+            warn!("Level {path} allows the unknown power-up `{name}`, it will never drop");
+        }
+    }
+}
+
+fn drop_power_up_observer(
+    trigger: Trigger<BlockBreakEvent>,
+    mut commands: Commands,
+    power_ups: Res<PowerUps>,
+    capsule_assets: Res<CapsuleAssets>,
+    game_mode: Res<GameMode>,
+    mut rng: ResMut<GameRng>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    blocks: Query<&Transform>,
+) {
+    let Some(level) = levels.get(&current_level.0) else {
+        return;
+    };
+    let Ok(block_transform) = blocks.get(trigger.block) else {
+        return;
+    };
+
+    if rng.random::<f32>() >= level.drop_chance {
+        return;
+    };
+
+    let droppable = power_ups.droppable(level);
+    if droppable.is_empty() {
+        return;
+    };
+
+    let id = droppable[rng.random_range(0..droppable.len())];
+    let Some(power_up) = power_ups.get(id) else {
+        return;
+    };
+
+    // The capsule falls towards the player who broke the block.
+    let direction = -trigger
+        .player
+        .as_ref()
+        .map_or(1.0, |player| game_mode.facing(player));
+    commands.spawn((
+        Capsule { id, direction },
+        Mesh2d(capsule_assets.mesh.clone()),
+        MeshMaterial2d(capsule_assets.materials[id.0].clone()),
+        Transform::from_translation(block_transform.translation)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)),
+    ));
+
+    debug!("Dropped power-up {}", power_up.name());
+}
+
+fn fall_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    playfield: Res<Playfield>,
+    capsules: Query<(Entity, &Capsule, &mut Transform)>,
+) {
+    let half_height = playfield.half_size().y;
+
+    for (entity, capsule, mut transform) in capsules {
+        transform.translation.y += CAPSULE_FALL_SPEED * capsule.direction * time.delta_secs();
+
+        if transform.translation.y.abs() - CAPSULE_LENGTH > half_height {
+            commands.entity(entity).despawn();
+        };
+    }
+}
+
+fn catch_capsule_system(
+    mut commands: Commands,
+    power_ups: Res<PowerUps>,
+    player_size: Res<PlayerSize>,
+    capsules: Query<(Entity, &Capsule, &Transform)>,
+    mut paddles: Query<(Entity, &Player, &Transform, &mut ActivePowerUps)>,
+) {
+    // The capsule lies on its side, so its length is horizontal.
+    let capsule_half_size = Vec2::new(CAPSULE_LENGTH / 2.0 + CAPSULE_RADIUS, CAPSULE_RADIUS);
+
+    for (capsule_entity, capsule, capsule_transform) in capsules.iter() {
+        for (paddle, player, paddle_transform, mut active) in paddles.iter_mut() {
+            let paddle_half_size = **player_size * paddle_transform.scale.truncate() / 2.0;
+            let distance = (capsule_transform.translation - paddle_transform.translation)
+                .truncate()
+                .abs();
+            if distance.x > paddle_half_size.x + capsule_half_size.x
+                || distance.y > paddle_half_size.y + capsule_half_size.y
+            {
+                continue;
+            };
+
+            commands.entity(capsule_entity).despawn();
+            activate(
+                &mut commands,
+                &power_ups,
+                capsule.id,
+                paddle,
+                player,
+                &mut active,
+            );
+            break;
+        }
+    }
+}
+
+fn activate(
+    commands: &mut Commands,
+    power_ups: &PowerUps,
+    id: PowerUpId,
+    paddle: Entity,
+    player: &Player,
+    active: &mut ActivePowerUps,
+) {
+    let Some(power_up) = power_ups.get(id) else {
+        return;
+    };
+
+    commands.trigger(PowerUpCollected {
+        player: player.clone(),
+        name: power_up.name(),
+    });
+
+    let Some(duration) = power_up.duration() else {
+        let power_up = power_up.clone();
+        commands.queue(move |world: &mut World| power_up.apply(world, paddle));
+        return;
+    };
+
+    // Catching an active power-up again only restarts its timer.
+    if let Some(existing) = active.0.iter_mut().find(|existing| existing.id == id) {
+        existing.timer = Timer::new(duration, TimerMode::Once);
+        return;
+    };
+
+    if let Some(group) = power_up.group() {
+        active.0.retain(|existing| {
+            let Some(other) = power_ups.get(existing.id) else {
+                return false;
+            };
+            if other.group() != Some(group) {
+                return true;
+            };
+
+            expire(commands, other, paddle, player);
+            false
+        });
+    };
+
+    active.0.push(ActivePowerUp {
+        id,
+        timer: Timer::new(duration, TimerMode::Once),
+    });
+
+    let power_up = power_up.clone();
+    commands.queue(move |world: &mut World| power_up.apply(world, paddle));
+}
+
+fn expire(commands: &mut Commands, power_up: &Arc<dyn PowerUp>, paddle: Entity, player: &Player) {
+    commands.trigger(PowerUpExpired {
+        player: player.clone(),
+        name: power_up.name(),
+    });
+
+    let power_up = power_up.clone();
+    commands.queue(move |world: &mut World| power_up.expire(world, paddle));
+}
+
+fn expire_power_ups_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    power_ups: Res<PowerUps>,
+    mut paddles: Query<(Entity, &Player, &mut ActivePowerUps)>,
+) {
+    for (paddle, player, mut active) in paddles.iter_mut() {
+        // Only take a mutable borrow if a timer finished, to keep change detection meaningful.
+        let untracked = active.bypass_change_detection();
+        for existing in untracked.0.iter_mut() {
+            existing.timer.tick(time.delta());
+        }
+        if !untracked.0.iter().any(|existing| existing.timer.finished()) {
+            continue;
+        };
+
+        active.0.retain(|existing| {
+            if !existing.timer.finished() {
+                return true;
+            };
+
+            if let Some(power_up) = power_ups.get(existing.id) {
+                expire(&mut commands, power_up, paddle, player);
+            };
+            false
+        });
+    }
+}
+
+fn log_collected_observer(trigger: Trigger<PowerUpCollected>) {
+    info!("{:?} collected {}", trigger.player, trigger.name);
+}
+
+fn log_expired_observer(trigger: Trigger<PowerUpExpired>) {
+    debug!("{} of {:?} expired", trigger.name, trigger.player);
+}
+
+fn despawn_capsules_system(mut commands: Commands, capsules: Query<Entity, With<Capsule>>) {
+    for capsule in capsules {
+        commands.entity(capsule).despawn();
+    }
+}
@@ -0,0 +1,408 @@
+use std::time::Duration;
+
+use avian2d::prelude::{CollisionStarted, SpatialQuery, SpatialQueryFilter};
+use bevy::prelude::*;
+
+use crate::{
+    app_state::AppState,
+    ball::{Ball, BallSpeedFactor, Held, SpawnBallEvent},
+    game_mode::GameMode,
+    lives::{LifeGained, Lives},
+    player::{Player, PlayerSize},
+    power_up::{ActivePowerUps, PowerUp, PowerUps, RegisterPowerUp},
+    simulation::SimulationSet,
+    world::block::{Block, BlockHitEvent},
+};
+
+const PADDLE_WIDTH_GROUP: &str = "paddle_width";
+const BALL_SPEED_GROUP: &str = "ball_speed";
+
+const STICKY_HOLD_TIME: Duration = Duration::from_millis(1500);
+const LASER_COOLDOWN: Duration = Duration::from_millis(400);
+const LASER_BEAM_LIFETIME: Duration = Duration::from_millis(80);
+const LASER_BEAM_WIDTH: f32 = 3.0;
+const LASER_RANGE: f32 = 2000.0;
+
+/// Registers the built-in power-ups and the systems that drive them.
+#[derive(Debug)]
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_power_up(WidePaddle)
+            .register_power_up(NarrowPaddle)
+            .register_power_up(MultiBall)
+            .register_power_up(SlowBall)
+            .register_power_up(StickyPaddle)
+            .register_power_up(LaserPaddle)
+            .register_power_up(ExtraLife);
+
+        app.add_systems(Startup, setup)
+            .add_systems(OnExit(AppState::InGame), despawn_laser_beams_system)
+            .add_systems(
+                FixedUpdate,
+                (
+                    stick_ball_system,
+                    fire_laser_system,
+                    despawn_laser_beams_after_lifetime_system,
+                )
+                    .in_set(SimulationSet::PowerUps),
+            );
+    }
+}
+
+#[derive(Debug)]
+struct WidePaddle;
+
+#[derive(Debug)]
+struct NarrowPaddle;
+
+#[derive(Debug)]
+struct MultiBall;
+
+#[derive(Debug)]
+struct SlowBall;
+
+#[derive(Debug)]
+struct StickyPaddle;
+
+#[derive(Debug)]
+struct LaserPaddle;
+
+#[derive(Debug)]
+struct ExtraLife;
+
+/// Balls that touch a sticky paddle rest on it for a moment.
+#[derive(Debug, Component)]
+pub struct Sticky;
+
+/// The paddle shoots laser beams that break any block, including steel.
+#[derive(Debug, Component)]
+pub struct Laser(Timer);
+
+#[derive(Debug, Component)]
+pub struct LaserBeam(Timer);
+
+/// Shared by every laser beam. The mesh is one unit long and scaled to the length of the beam.
+#[derive(Debug, Resource)]
+struct LaserBeamAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+}
+
+impl PowerUp for WidePaddle {
+    fn name(&self) -> &'static str {
+        "wide_paddle"
+    }
+
+    fn color(&self) -> Color {
+        Color::linear_rgb(0.1, 0.8, 0.1)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(15))
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(PADDLE_WIDTH_GROUP)
+    }
+
+    fn apply(&self, world: &mut World, paddle: Entity) {
+        set_paddle_width_scale(world, paddle, 1.5);
+    }
+
+    fn expire(&self, world: &mut World, paddle: Entity) {
+        set_paddle_width_scale(world, paddle, 1.0);
+    }
+}
+
+impl PowerUp for NarrowPaddle {
+    fn name(&self) -> &'static str {
+        "narrow_paddle"
+    }
+
+    fn color(&self) -> Color {
+        Color::linear_rgb(0.8, 0.1, 0.1)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(10))
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(PADDLE_WIDTH_GROUP)
+    }
+
+    fn apply(&self, world: &mut World, paddle: Entity) {
+        set_paddle_width_scale(world, paddle, 0.6);
+    }
+
+    fn expire(&self, world: &mut World, paddle: Entity) {
+        set_paddle_width_scale(world, paddle, 1.0);
+    }
+}
+
+impl PowerUp for MultiBall {
+    fn name(&self) -> &'static str {
+        "multi_ball"
+    }
+
+    fn color(&self) -> Color {
+        Color::linear_rgb(0.9, 0.9, 0.9)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        None
+    }
+
+    fn apply(&self, world: &mut World, _paddle: Entity) {
+        // Prefer a ball in flight so the new balls do not start resting on a paddle.
+        let mut balls = world.query_filtered::<(Entity, Has<Held>), With<Ball>>();
+        let Some((ball, _)) = balls.iter(world).min_by_key(|(_, held)| *held) else {
+            return;
+        };
+
+        world.trigger(SpawnBallEvent::Split { ball, count: 3 });
+    }
+}
+
+impl PowerUp for SlowBall {
+    fn name(&self) -> &'static str {
+        "slow_ball"
+    }
+
+    fn color(&self) -> Color {
+        Color::linear_rgb(0.1, 0.4, 0.9)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(10))
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(BALL_SPEED_GROUP)
+    }
+
+    fn apply(&self, world: &mut World, _paddle: Entity) {
+        world.resource_mut::<BallSpeedFactor>().0 = 0.6;
+    }
+
+    fn expire(&self, world: &mut World, _paddle: Entity) {
+        // Every ball shares the speed, so it stays slow while another paddle has the power-up.
+        if is_active_on_any_paddle(world, self.name()) {
+            return;
+        };
+        world.resource_mut::<BallSpeedFactor>().0 = 1.0;
+    }
+}
+
+impl PowerUp for StickyPaddle {
+    fn name(&self) -> &'static str {
+        "sticky_paddle"
+    }
+
+    fn color(&self) -> Color {
+        Color::linear_rgb(0.9, 0.8, 0.1)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(15))
+    }
+
+    fn apply(&self, world: &mut World, paddle: Entity) {
+        if let Ok(mut paddle) = world.get_entity_mut(paddle) {
+            paddle.insert(Sticky);
+        };
+    }
+
+    fn expire(&self, world: &mut World, paddle: Entity) {
+        if let Ok(mut paddle) = world.get_entity_mut(paddle) {
+            paddle.remove::<Sticky>();
+        };
+    }
+}
+
+impl PowerUp for LaserPaddle {
+    fn name(&self) -> &'static str {
+        "laser_paddle"
+    }
+
+    fn color(&self) -> Color {
+        Color::linear_rgb(0.8, 0.1, 0.8)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(10))
+    }
+
+    fn apply(&self, world: &mut World, paddle: Entity) {
+        if let Ok(mut paddle) = world.get_entity_mut(paddle) {
+            paddle.insert(Laser(Timer::new(LASER_COOLDOWN, TimerMode::Repeating)));
+        };
+    }
+
+    fn expire(&self, world: &mut World, paddle: Entity) {
+        if let Ok(mut paddle) = world.get_entity_mut(paddle) {
+            paddle.remove::<Laser>();
+        };
+    }
+}
+
+impl PowerUp for ExtraLife {
+    fn name(&self) -> &'static str {
+        "extra_life"
+    }
+
+    fn color(&self) -> Color {
+        Color::linear_rgb(1.0, 0.4, 0.7)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        None
+    }
+
+    fn apply(&self, world: &mut World, paddle: Entity) {
+        let game_mode = *world.resource::<GameMode>();
+        // Competitive modes give the life to the player who caught it, the others share it.
+        let player = world
+            .get::<Player>(paddle)
+            .filter(|_| game_mode.is_competitive())
+            .cloned();
+
+        let mut lives = world.resource_mut::<Lives>();
+        let remaining = lives.of_mut(game_mode, player.as_ref());
+        *remaining += 1;
+        let remaining = *remaining;
+
+        world.trigger(LifeGained { remaining, player });
+    }
+}
+
+fn set_paddle_width_scale(world: &mut World, paddle: Entity, scale: f32) {
+    if let Some(mut transform) = world.get_mut::<Transform>(paddle) {
+        transform.scale.x = scale;
+    };
+}
+
+fn stick_ball_system(
+    mut commands: Commands,
+    mut collision_started: EventReader<CollisionStarted>,
+    balls: Query<&Transform, (With<Ball>, Without<Held>)>,
+    paddles: Query<&Transform, (With<Player>, With<Sticky>)>,
+) {
+    for &CollisionStarted(a, b) in collision_started.read() {
+        let (ball, paddle) = match (
+            balls.contains(a) && paddles.contains(b),
+            balls.contains(b) && paddles.contains(a),
+        ) {
+            (true, _) => (a, b),
+            (_, true) => (b, a),
+            _ => continue,
+        };
+
+        let (Ok(ball_transform), Ok(paddle_transform)) = (balls.get(ball), paddles.get(paddle))
+        else {
+            continue;
+        };
+
+        commands.entity(ball).insert(Held {
+            paddle,
+            offset: ball_transform.translation.x - paddle_transform.translation.x,
+            release: Timer::new(STICKY_HOLD_TIME, TimerMode::Once),
+        });
+    }
+}
+
+/// Expired power-ups are removed from their paddle before [`PowerUp::expire`] runs.
+fn is_active_on_any_paddle(world: &mut World, name: &str) -> bool {
+    let Some(id) = world.resource::<PowerUps>().find(name) else {
+        return false;
+    };
+
+    world
+        .query::<&ActivePowerUps>()
+        .iter(world)
+        .any(|active| active.0.iter().any(|existing| existing.id == id))
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(LaserBeamAssets {
+        mesh: meshes.add(Rectangle::new(LASER_BEAM_WIDTH, 1.0)),
+        material: materials.add(Color::linear_rgb(1.0, 0.2, 0.9)),
+    });
+}
+
+fn fire_laser_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    player_size: Res<PlayerSize>,
+    game_mode: Res<GameMode>,
+    spatial_query: SpatialQuery,
+    beam_assets: Res<LaserBeamAssets>,
+    mut paddles: Query<(&mut Laser, &Transform, &Player)>,
+    blocks: Query<(), With<Block>>,
+) {
+    for (mut laser, transform, player) in paddles.iter_mut() {
+        if !laser.0.tick(time.delta()).just_finished() {
+            continue;
+        };
+
+        // Beams leave from the side of the paddle that faces the blocks.
+        let facing = game_mode.facing(player);
+        let half_width = player_size.x * transform.scale.x / 2.0;
+        let front = transform.translation.y + player_size.y / 2.0 * facing;
+        let direction = if facing < 0.0 { Dir2::NEG_Y } else { Dir2::Y };
+
+        // One beam from each end of the paddle.
+        for x in [-half_width, half_width].map(|x| transform.translation.x + x * 0.8) {
+            let origin = Vec2::new(x, front);
+            let hit = spatial_query.cast_ray_predicate(
+                origin,
+                direction,
+                LASER_RANGE,
+                true,
+                &SpatialQueryFilter::default(),
+                &|entity| blocks.contains(entity),
+            );
+
+            let length = hit.map_or(LASER_RANGE, |hit| hit.distance);
+            if let Some(hit) = hit {
+                commands.trigger(BlockHitEvent {
+                    block: hit.entity,
+                    breaks_steel: true,
+                    player: Some(player.clone()),
+                });
+            };
+
+            commands.spawn((
+                LaserBeam(Timer::new(LASER_BEAM_LIFETIME, TimerMode::Once)),
+                Mesh2d(beam_assets.mesh.clone()),
+                MeshMaterial2d(beam_assets.material.clone()),
+                Transform::from_xyz(x, front + length / 2.0 * facing, -1.0)
+                    .with_scale(Vec3::new(1.0, length, 1.0)),
+            ));
+        }
+    }
+}
+
+fn despawn_laser_beams_after_lifetime_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    beams: Query<(Entity, &mut LaserBeam)>,
+) {
+    for (entity, mut beam) in beams {
+        if beam.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        };
+    }
+}
+
+fn despawn_laser_beams_system(mut commands: Commands, beams: Query<Entity, With<LaserBeam>>) {
+    for beam in beams {
+        commands.entity(beam).despawn();
+    }
+}
@@ -0,0 +1,276 @@
+//! Records the inputs of a session and plays them back. The simulation is deterministic, so the
+//! seed, the state a level started in and the input of every step are enough to play the level
+//! again exactly as it happened.
+//!
+//! `--record FILE` writes the session to `FILE` whenever a level ends, so the file holds every
+//! level played so far. `--replay FILE` plays the levels back one after another and reports if the
+//! blocks break differently than they did while recording.
+
+use std::{fs, io, path::Path, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    game_mode::GameMode,
+    input::PaddleInput,
+    lives::Lives,
+    player::{PerPlayer, Player},
+    replay::{playback::PlaybackPlugin, record::RecordPlugin},
+};
+
+pub mod playback;
+pub mod record;
+
+/// Bumped whenever [`Session`], [`Replay`] or the simulation changes in a way that breaks old
+/// replays.
+pub const REPLAY_VERSION: u32 = 2;
+
+/// Adds recording or playback, depending on the [`ReplayMode`].
+#[derive(Debug, Default)]
+pub struct ReplayPlugin {
+    pub mode: ReplayMode,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match &self.mode {
+            ReplayMode::Off => (),
+            ReplayMode::Record(path) => {
+                app.add_plugins(RecordPlugin { path: path.clone() });
+            }
+            ReplayMode::Play(path) => {
+                app.add_plugins(PlaybackPlugin { path: path.clone() });
+            }
+        };
+    }
+}
+
+/// Whether this game records or plays back a replay, read from the command line.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    Record(PathBuf),
+    Play(PathBuf),
+}
+
+/// Every level played since the game started, in the order they were played.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub levels: Vec<Replay>,
+}
+
+/// One level, from the moment it started until it ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub game_mode: GameMode,
+    /// Zero based index of the level in the campaign.
+    pub level: usize,
+    pub level_name: String,
+    /// The size of the playfield. Blocks and paddles scale with it, so a replay only plays out the
+    /// same on a field of the same size.
+    pub field_size: [f32; 2],
+    /// The lives when the level started.
+    pub lives: Lives,
+    /// The player whose paddle the first ball rested on.
+    pub serving: Player,
+    pub inputs: Vec<InputRun>,
+    /// Every broken block, in the order they broke.
+    pub breaks: Vec<BlockBreak>,
+}
+
+/// The input of `ticks` simulation steps in a row. Players hold the same keys for many steps, so
+/// storing runs keeps replays small.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InputRun {
+    pub ticks: u32,
+    pub inputs: PerPlayer<PaddleInput>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlockBreak {
+    /// The simulation step the block broke in, counted from the start of the level.
+    pub tick: u64,
+    pub position: [f32; 2],
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Could not access the replay file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Could not encode the replay: {0}")]
+    Encode(#[from] bincode::error::EncodeError),
+    #[error("Replay file is malformed: {0}")]
+    Decode(#[from] bincode::error::DecodeError),
+    #[error("The replay has version {0}, this game plays version {REPLAY_VERSION}")]
+    Version(u32),
+    #[error("The replay does not contain any levels")]
+    Empty,
+}
+
+impl Replay {
+    /// Appends the input of the next simulation step.
+    pub fn push_input(&mut self, inputs: PerPlayer<PaddleInput>) {
+        match self.inputs.last_mut() {
+            Some(run) if run.inputs == inputs && run.ticks < u32::MAX => run.ticks += 1,
+            _ => self.inputs.push(InputRun { ticks: 1, inputs }),
+        };
+    }
+
+    /// How many simulation steps the replay lasts.
+    pub fn tick_count(&self) -> u64 {
+        self.inputs.iter().map(|run| u64::from(run.ticks)).sum()
+    }
+}
+
+impl Session {
+    /// Writes the session to `path`, prefixed with the [`REPLAY_VERSION`].
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        let config = bincode::config::standard();
+        let mut bytes = bincode::serde::encode_to_vec(REPLAY_VERSION, config)?;
+        bytes.extend(bincode::serde::encode_to_vec(self, config)?);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        };
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Reads a session with at least one level.
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let config = bincode::config::standard();
+        let bytes = fs::read(path)?;
+
+        // Check the version first, replays of other versions do not decode.
+        let (version, read) = bincode::serde::decode_from_slice::<u32, _>(&bytes, config)?;
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::Version(version));
+        };
+
+        let (session, _) = bincode::serde::decode_from_slice::<Self, _>(&bytes[read..], config)?;
+        if session.levels.is_empty() {
+            return Err(ReplayError::Empty);
+        };
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay() -> Replay {
+        Replay {
+            seed: 42,
+            game_mode: GameMode::Coop,
+            level: 1,
+            level_name: "Test".to_string(),
+            field_size: [1280.0, 720.0],
+            lives: Lives::default(),
+            serving: Player::Second,
+            inputs: Vec::new(),
+            breaks: vec![BlockBreak {
+                tick: 12,
+                position: [-40.0, 200.0],
+            }],
+        }
+    }
+
+    fn input(axis: f32, launch: bool) -> PerPlayer<PaddleInput> {
+        PerPlayer {
+            first: PaddleInput {
+                axis,
+                target: None,
+                launch,
+            },
+            second: PaddleInput::default(),
+        }
+    }
+
+    /// A path in the temporary directory that no other test uses.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("breakout-{}-{name}.replay", std::process::id()))
+    }
+
+    #[test]
+    fn merges_identical_inputs_into_runs() {
+        let mut replay = replay();
+        for inputs in [
+            input(0.0, false),
+            input(0.0, false),
+            input(1.0, false),
+            input(1.0, true),
+            input(1.0, true),
+            input(1.0, true),
+            input(0.0, false),
+        ] {
+            replay.push_input(inputs);
+        }
+
+        let runs = replay
+            .inputs
+            .iter()
+            .map(|run| (run.ticks, run.inputs))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            runs,
+            [
+                (2, input(0.0, false)),
+                (1, input(1.0, false)),
+                (3, input(1.0, true)),
+                (1, input(0.0, false)),
+            ]
+        );
+        assert_eq!(replay.tick_count(), 7);
+    }
+
+    #[test]
+    fn loads_what_it_saved() {
+        let mut first = replay();
+        first.push_input(input(-0.5, false));
+        first.push_input(input(0.25, true));
+        let session = Session {
+            levels: vec![first, replay()],
+        };
+
+        let path = temp_path("round-trip");
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), session);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let config = bincode::config::standard();
+        let mut bytes = bincode::serde::encode_to_vec(REPLAY_VERSION + 1, config).unwrap();
+        let session = Session {
+            levels: vec![replay()],
+        };
+        bytes.extend(bincode::serde::encode_to_vec(&session, config).unwrap());
+
+        let path = temp_path("version");
+        fs::write(&path, bytes).unwrap();
+        let loaded = Session::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(
+            matches!(loaded, Err(ReplayError::Version(version)) if version == REPLAY_VERSION + 1)
+        );
+    }
+
+    #[test]
+    fn rejects_empty_sessions() {
+        let path = temp_path("empty");
+        Session::default().save(&path).unwrap();
+        let loaded = Session::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(loaded, Err(ReplayError::Empty)));
+    }
+}
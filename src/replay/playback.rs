@@ -0,0 +1,485 @@
+use std::path::PathBuf;
+
+use bevy::{
+    app::{FixedMain, RunFixedMainLoopSystem},
+    prelude::*,
+};
+
+use crate::{
+    StartGame,
+    app_state::{AppState, GameState},
+    ball::ServingPlayer,
+    game_mode::GameMode,
+    headless::Headless,
+    input::{PaddleInput, PlayerInputs},
+    lives::Lives,
+    player::PerPlayer,
+    replay::{Replay, Session},
+    simulation::{SimulationConfig, SimulationTick},
+    ui,
+    world::{Playfield, block::BlockBreakEvent, campaign::Campaign},
+};
+
+const PAUSE_KEY: KeyCode = KeyCode::Space;
+const STEP_KEY: KeyCode = KeyCode::Period;
+const FAST_FORWARD_KEY: KeyCode = KeyCode::KeyF;
+const FAST_FORWARD_SPEED: f32 = 4.0;
+/// Blocks within this distance of the recorded position count as the same block.
+const POSITION_TOLERANCE: f32 = 0.5;
+
+/// Plays the levels of the replay at `path` one after another instead of taking input from the
+/// players. In a headless app it plays on a field of the recorded size and exits once the last
+/// level is over, with an error if any of them diverged.
+#[derive(Debug)]
+pub struct PlaybackPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        match Session::load(&self.path) {
+            Ok(session) => {
+                info!(
+                    "Loaded the replay {} with {} levels",
+                    self.path.display(),
+                    session.levels.len()
+                );
+                if app.world().contains_resource::<Headless>() {
+                    app.insert_resource(Playfield {
+                        size: Vec2::from(session.levels[0].field_size),
+                    });
+                };
+                app.insert_resource(Playback::new(session));
+            }
+            Err(error) => {
+                error!("Could not load the replay {}: {error}", self.path.display());
+                if app.world().contains_resource::<Headless>() {
+                    app.add_systems(Startup, |mut exit: EventWriter<AppExit>| {
+                        exit.write(AppExit::error());
+                    });
+                };
+                return;
+            }
+        };
+
+        app.add_observer(check_break_observer);
+
+        // Starting the game needs the resources the other plugins insert on startup.
+        app.add_systems(PostStartup, start_playback_system)
+            .add_systems(OnEnter(AppState::InGame), playback_bar_setup)
+            .add_systems(
+                OnExit(AppState::InGame),
+                (ui::despawn_screen::<PlaybackBar>, end_playback_system),
+            )
+            .add_systems(
+                FixedFirst,
+                play_input_system
+                    .run_if(in_state(GameState::Running).and(resource_exists::<Playback>)),
+            )
+            .add_systems(
+                RunFixedMainLoop,
+                step_system
+                    .in_set(RunFixedMainLoopSystem::AfterFixedMainLoop)
+                    .run_if(step_requested),
+            )
+            .add_systems(
+                Update,
+                (
+                    playback_controls_system,
+                    finish_playback_system,
+                    update_playback_text_system,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame).and(resource_exists::<Playback>)),
+            );
+    }
+}
+
+/// The replay that is being played back and how far its current level got. Removed once the last
+/// level ends.
+#[derive(Debug, Resource)]
+pub struct Playback {
+    session: Session,
+    /// The index of the level in [`Session::levels`] that is played.
+    level: usize,
+    /// The run of [`Replay::inputs`] the next step takes its input from.
+    run: usize,
+    /// How many steps of that run were played.
+    run_ticks: u32,
+    played: u64,
+    /// How many blocks broke so far.
+    breaks: usize,
+    /// The tick the blocks first broke differently than recorded.
+    diverged: Option<u64>,
+    /// Every input was played.
+    finished: bool,
+    reported: bool,
+    /// Every level reported so far played out as recorded.
+    all_matched: bool,
+    /// The level is left to start the next one, that does not end the playback.
+    leaving: bool,
+    /// Play one step while paused.
+    step: bool,
+}
+
+#[derive(Debug, Component)]
+struct PlaybackBar;
+
+#[derive(Debug, Component)]
+struct PlaybackText;
+
+impl Playback {
+    fn new(session: Session) -> Self {
+        Self {
+            session,
+            level: 0,
+            run: 0,
+            run_ticks: 0,
+            played: 0,
+            breaks: 0,
+            diverged: None,
+            finished: false,
+            reported: false,
+            all_matched: true,
+            leaving: false,
+            step: false,
+        }
+    }
+
+    fn replay(&self) -> &Replay {
+        &self.session.levels[self.level]
+    }
+
+    /// Moves on to the next level, `false` if this was the last one.
+    fn next_level(&mut self) -> bool {
+        if self.level + 1 >= self.session.levels.len() {
+            return false;
+        };
+
+        *self = Self {
+            session: std::mem::take(&mut self.session),
+            level: self.level + 1,
+            all_matched: self.all_matched,
+            ..Self::new(Session::default())
+        };
+        true
+    }
+
+    /// The level that is played and how many there are, for the log and the playback bar.
+    fn level_label(&self) -> String {
+        format!("level {} of {}", self.level + 1, self.session.levels.len())
+    }
+
+    /// The input of the next step, `None` once every step was played.
+    fn next_input(&mut self) -> Option<PerPlayer<PaddleInput>> {
+        let run = self.replay().inputs.get(self.run)?;
+        let (inputs, ticks) = (run.inputs, run.ticks);
+
+        self.run_ticks += 1;
+        if self.run_ticks >= ticks {
+            self.run += 1;
+            self.run_ticks = 0;
+        };
+        self.played += 1;
+
+        Some(inputs)
+    }
+
+    /// Whether the replay played out as recorded, for the log and the playback bar.
+    fn summary(&self) -> String {
+        let recorded = self.replay().breaks.len();
+        match self.diverged {
+            Some(tick) => format!("Diverged on tick {tick}"),
+            None if self.breaks < recorded => {
+                format!("Diverged, {} of {recorded} blocks broke", self.breaks)
+            }
+            None => format!("All {recorded} blocks broke as recorded"),
+        }
+    }
+
+    fn matches(&self) -> bool {
+        self.diverged.is_none() && self.breaks == self.replay().breaks.len()
+    }
+
+    /// Logs the summary of the current level.
+    fn report(&mut self) {
+        self.reported = true;
+        self.all_matched &= self.matches();
+
+        if self.matches() {
+            info!(
+                "Replay of {} finished. {}",
+                self.level_label(),
+                self.summary()
+            );
+        } else {
+            warn!(
+                "Replay of {} finished. {}",
+                self.level_label(),
+                self.summary()
+            );
+        };
+    }
+
+    /// Headless apps exit once the last level was reported, with an error if any level diverged.
+    fn exit(&self, headless: Option<Res<Headless>>, exit: &mut EventWriter<AppExit>) {
+        if headless.is_some() {
+            exit.write(if self.all_matched {
+                AppExit::Success
+            } else {
+                AppExit::error()
+            });
+        };
+    }
+}
+
+fn start_playback_system(mut commands: Commands, playback: Res<Playback>) {
+    start_level(&mut commands, &playback);
+}
+
+/// Starts the current level of the playback the way it started while recording.
+fn start_level(commands: &mut Commands, playback: &Playback) {
+    let replay = playback.replay().clone();
+    info!(
+        "Playing back {}: {} ticks of {} in {}",
+        playback.level_label(),
+        replay.tick_count(),
+        replay.level_name,
+        replay.game_mode.name()
+    );
+
+    let (game_mode, seed, field_size) =
+        (replay.game_mode, replay.seed, Vec2::from(replay.field_size));
+    commands.queue(move |world: &mut World| {
+        let playfield = world.resource::<Playfield>().size;
+        if playfield != field_size {
+            warn!(
+                "The replay was recorded on a {}x{} field, this one is {}x{}. It will likely diverge.",
+                field_size.x, field_size.y, playfield.x, playfield.y
+            );
+        };
+
+        *world.resource_mut::<GameMode>() = game_mode;
+        world.resource_mut::<SimulationConfig>().seed = Some(seed);
+    });
+    commands.trigger(StartGame);
+
+    // Starting the game resets the campaign, the lives and the serving player.
+    let (level, lives, serving) = (replay.level, replay.lives, replay.serving);
+    commands.queue(move |world: &mut World| {
+        world.resource_mut::<Campaign>().select(level);
+        *world.resource_mut::<Lives>() = lives;
+        world.resource_mut::<ServingPlayer>().0 = serving;
+    });
+}
+
+fn play_input_system(mut playback: ResMut<Playback>, mut inputs: ResMut<PlayerInputs>) {
+    match playback.next_input() {
+        Some(next) => inputs.0 = next,
+        // The rest of the frame runs without input, the game pauses once it is over.
+        None => {
+            *inputs = PlayerInputs::default();
+            playback.finished = true;
+        }
+    };
+}
+
+fn check_break_observer(
+    trigger: Trigger<BlockBreakEvent>,
+    playback: Option<ResMut<Playback>>,
+    tick: Res<SimulationTick>,
+    blocks: Query<&Transform>,
+) {
+    let (Some(mut playback), Ok(transform)) = (playback, blocks.get(trigger.block)) else {
+        return;
+    };
+    let position = transform.translation.truncate();
+
+    let index = playback.breaks;
+    playback.breaks += 1;
+    if playback.diverged.is_some() {
+        return;
+    };
+
+    match playback.replay().breaks.get(index) {
+        Some(expected)
+            if expected.tick == tick.0
+                && Vec2::from(expected.position).distance(position) <= POSITION_TOLERANCE => {}
+        Some(expected) => {
+            warn!(
+                "Replay diverged: block {} broke on tick {} at {position}, it was recorded on tick {} at {}",
+                index + 1,
+                tick.0,
+                expected.tick,
+                Vec2::from(expected.position)
+            );
+            playback.diverged = Some(tick.0);
+        }
+        None => {
+            warn!(
+                "Replay diverged: block {} broke on tick {} at {position}, but only {index} were recorded",
+                index + 1,
+                tick.0
+            );
+            playback.diverged = Some(tick.0);
+        }
+    };
+}
+
+fn playback_bar_setup(mut commands: Commands, playback: Option<Res<Playback>>) {
+    if playback.is_none() {
+        return;
+    };
+
+    commands.spawn((
+        PlaybackBar,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            right: Val::Px(0.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..Default::default()
+        },
+        children![(ui::label(String::new()), PlaybackText)],
+    ));
+}
+
+fn playback_controls_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut playback: ResMut<Playback>,
+) {
+    if playback.finished {
+        return;
+    };
+
+    if keys.just_pressed(PAUSE_KEY) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        };
+    };
+    if keys.just_pressed(FAST_FORWARD_KEY) {
+        let speed = if time.relative_speed() == 1.0 {
+            FAST_FORWARD_SPEED
+        } else {
+            1.0
+        };
+        time.set_relative_speed(speed);
+    };
+    if keys.just_pressed(STEP_KEY) && time.is_paused() {
+        playback.step = true;
+    };
+}
+
+/// Starts the next level once every step of the current one was played. Stops at the last step of
+/// the last level, so the final state of the replay stays on screen.
+fn finish_playback_system(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    mut playback: ResMut<Playback>,
+    headless: Option<Res<Headless>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if !playback.finished || playback.reported {
+        return;
+    };
+
+    playback.report();
+    if playback.next_level() {
+        playback.leaving = true;
+        start_level(&mut commands, &playback);
+    } else {
+        time.pause();
+        playback.exit(headless, &mut exit);
+    };
+}
+
+fn update_playback_text_system(
+    playback: Res<Playback>,
+    time: Res<Time<Virtual>>,
+    mut texts: Query<&mut Text, With<PlaybackText>>,
+) {
+    let mut parts = vec![format!(
+        "REPLAY {}    {} / {}",
+        playback.level_label().to_uppercase(),
+        playback.played,
+        playback.replay().tick_count()
+    )];
+    if playback.finished {
+        parts.push("FINISHED".to_string());
+    } else if time.is_paused() {
+        parts.push("PAUSED".to_string());
+    } else if time.relative_speed() != 1.0 {
+        parts.push(format!("x{}", time.relative_speed()));
+    };
+    if playback.finished || playback.diverged.is_some() {
+        parts.push(playback.summary());
+    };
+    let text = parts.join("    ");
+
+    for mut playback_text in texts.iter_mut() {
+        playback_text.set_if_neq(Text(text.clone()));
+    }
+}
+
+fn step_requested(playback: Option<Res<Playback>>) -> bool {
+    playback.is_some_and(|playback| playback.step)
+}
+
+/// Runs one simulation step by hand, since the fixed timestep does not advance while the virtual
+/// clock is paused.
+fn step_system(world: &mut World) {
+    world.resource_mut::<Playback>().step = false;
+
+    let timestep = world.resource::<Time<Fixed>>().timestep();
+    world.resource_mut::<Time<Fixed>>().advance_by(timestep);
+    *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+    world.run_schedule(FixedMain);
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+}
+
+/// A level that ends before its last step, e.g. because it was cleared, moves on to the next level
+/// of the replay. Once the replay is over, or the level was left from the pause menu, whatever
+/// comes after it is played as usual.
+fn end_playback_system(
+    mut commands: Commands,
+    playback: Option<ResMut<Playback>>,
+    app_state: Res<State<AppState>>,
+    mut config: ResMut<SimulationConfig>,
+    mut time: ResMut<Time<Virtual>>,
+    headless: Option<Res<Headless>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    if playback.leaving {
+        playback.leaving = false;
+        return;
+    };
+
+    if !playback.reported {
+        playback.report();
+
+        // The state was already switched to the one that follows the level.
+        let level_ended = matches!(
+            app_state.get(),
+            AppState::LevelComplete
+                | AppState::CampaignWon
+                | AppState::GameOver
+                | AppState::RoundOver
+        );
+        if level_ended && playback.next_level() {
+            start_level(&mut commands, &playback);
+            return;
+        };
+        playback.exit(headless, &mut exit);
+    };
+
+    commands.remove_resource::<Playback>();
+    config.seed = None;
+    time.unpause();
+    time.set_relative_speed(1.0);
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::{
+    app_state::{AppState, GameState},
+    ball::ServingPlayer,
+    game_mode::GameMode,
+    input::PlayerInputs,
+    lives::Lives,
+    replay::{BlockBreak, Replay, Session},
+    simulation::{GameRng, SimulationTick},
+    world::{
+        Playfield,
+        block::BlockBreakEvent,
+        campaign::Campaign,
+        level::{CurrentLevel, Level},
+    },
+};
+
+/// Records every level and writes the whole session to `path` whenever one ends.
+#[derive(Debug)]
+pub struct RecordPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for RecordPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RecordPath(self.path.clone()))
+            .init_resource::<RecordedSession>();
+
+        app.add_observer(record_break_observer);
+
+        app.add_systems(OnEnter(AppState::InGame), start_recording_system)
+            .add_systems(OnExit(AppState::InGame), save_recording_system)
+            .add_systems(
+                FixedFirst,
+                record_input_system
+                    .run_if(in_state(GameState::Running).and(resource_exists::<Recording>)),
+            )
+            .add_systems(
+                Last,
+                save_recording_system.run_if(resource_exists::<Recording>.and(on_event::<AppExit>)),
+            );
+    }
+}
+
+#[derive(Debug, Resource)]
+struct RecordPath(PathBuf);
+
+/// The level that is being recorded.
+#[derive(Debug, Resource)]
+struct Recording(Replay);
+
+/// The levels that ended so far.
+#[derive(Debug, Default, Resource)]
+struct RecordedSession(Session);
+
+fn start_recording_system(
+    mut commands: Commands,
+    rng: Res<GameRng>,
+    game_mode: Res<GameMode>,
+    campaign: Res<Campaign>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    lives: Res<Lives>,
+    serving_player: Res<ServingPlayer>,
+    playfield: Res<Playfield>,
+) {
+    let level_name = levels
+        .get(&current_level.0)
+        .map(|level| level.name.clone())
+        .unwrap_or_default();
+
+    commands.insert_resource(Recording(Replay {
+        seed: rng.seed(),
+        game_mode: *game_mode,
+        level: campaign.current(),
+        level_name,
+        field_size: playfield.size.to_array(),
+        lives: lives.clone(),
+        serving: serving_player.0.clone(),
+        inputs: Vec::new(),
+        breaks: Vec::new(),
+    }));
+}
+
+/// Runs before the gameplay of every step, when the inputs of the step are complete.
+fn record_input_system(mut recording: ResMut<Recording>, inputs: Res<PlayerInputs>) {
+    recording.0.push_input(inputs.0);
+}
+
+fn record_break_observer(
+    trigger: Trigger<BlockBreakEvent>,
+    recording: Option<ResMut<Recording>>,
+    tick: Res<SimulationTick>,
+    blocks: Query<&Transform>,
+) {
+    let (Some(mut recording), Ok(transform)) = (recording, blocks.get(trigger.block)) else {
+        return;
+    };
+
+    recording.0.breaks.push(BlockBreak {
+        tick: tick.0,
+        position: transform.translation.truncate().to_array(),
+    });
+}
+
+/// Adds the level that ended to the session and writes all of it again, so the file is complete
+/// even if the game does not exit cleanly.
+fn save_recording_system(
+    mut commands: Commands,
+    path: Res<RecordPath>,
+    recording: Option<Res<Recording>>,
+    mut session: ResMut<RecordedSession>,
+) {
+    let Some(recording) = recording else {
+        return;
+    };
+    commands.remove_resource::<Recording>();
+
+    let replay = &recording.0;
+    session.0.levels.push(replay.clone());
+    match session.0.save(&path.0) {
+        Ok(()) => info!(
+            "Recorded {} ticks of {} to {}, level {} of the session",
+            replay.tick_count(),
+            replay.level_name,
+            path.0.display(),
+            session.0.levels.len()
+        ),
+        Err(error) => error!("Could not save the replay to {}: {error}", path.0.display()),
+    };
+}
@@ -0,0 +1,202 @@
+use avian2d::prelude::CollisionStarted;
+use bevy::prelude::*;
+
+use crate::{
+    RestartLevel, StartGame,
+    app_state::AppState,
+    ball::Ball,
+    player::{PerPlayer, Player},
+    simulation::SimulationSet,
+    world::block::{BlockBreakEvent, BlockHitEvent, BlockPoints},
+};
+
+/// Consecutive block hits needed to raise the combo multiplier by one.
+const COMBO_HITS_PER_STEP: u32 = 3;
+const MAX_COMBO_MULTIPLIER: u32 = 8;
+
+#[derive(Debug)]
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>().init_resource::<Combo>();
+
+        app.add_observer(reset_score_on_start_observer)
+            .add_observer(reset_score_on_restart_observer)
+            .add_observer(block_hit_combo_observer)
+            .add_observer(block_break_score_observer)
+            .add_observer(log_score_observer)
+            .add_observer(log_combo_observer);
+
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            start_level_system.in_set(StartLevelScore),
+        )
+        .add_systems(
+            FixedUpdate,
+            reset_combo_on_paddle_system.in_set(SimulationSet::Balls),
+        );
+    }
+}
+
+/// Resets the combo and remembers the score when a level starts. Systems that show them when the
+/// level starts run after it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct StartLevelScore;
+
+#[derive(Debug, Default, Resource)]
+pub struct Score {
+    pub points: u32,
+    /// The points of every player. Blocks count for the player whose ball broke them, so the sum
+    /// can be lower than `points`.
+    pub per_player: PerPlayer<u32>,
+    /// The points at the start of the current level. Restarting the level resets to them.
+    level_start: u32,
+    per_player_level_start: PerPlayer<u32>,
+}
+
+/// Consecutive block hits since the ball last touched a paddle. Hits that do not break the block
+/// count as well.
+#[derive(Debug, Default, Resource)]
+pub struct Combo {
+    pub hits: u32,
+}
+
+/// Triggered whenever the score changes.
+#[derive(Debug, Event)]
+pub struct ScoreChanged {
+    pub points: u32,
+    /// The player who scored. `None` if the score was reset or nobody touched the ball.
+    pub player: Option<Player>,
+    /// Points gained by this change. Zero if the score was reset.
+    pub gained: u32,
+    pub multiplier: u32,
+}
+
+/// Triggered whenever the combo grows or is reset.
+#[derive(Debug, Event)]
+pub struct ComboChanged {
+    pub hits: u32,
+    pub multiplier: u32,
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.hits / COMBO_HITS_PER_STEP).min(MAX_COMBO_MULTIPLIER)
+    }
+}
+
+fn reset_score_on_start_observer(
+    _: Trigger<StartGame>,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+) {
+    *score = Score::default();
+    commands.trigger(ScoreChanged {
+        points: score.points,
+        player: None,
+        gained: 0,
+        multiplier: 1,
+    });
+}
+
+fn reset_score_on_restart_observer(
+    _: Trigger<RestartLevel>,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+) {
+    score.points = score.level_start;
+    score.per_player = score.per_player_level_start;
+    commands.trigger(ScoreChanged {
+        points: score.points,
+        player: None,
+        gained: 0,
+        multiplier: 1,
+    });
+}
+
+fn start_level_system(mut score: ResMut<Score>, mut combo: ResMut<Combo>) {
+    score.level_start = score.points;
+    score.per_player_level_start = score.per_player;
+    *combo = Combo::default();
+}
+
+fn block_break_score_observer(
+    trigger: Trigger<BlockBreakEvent>,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    combo: Res<Combo>,
+    blocks: Query<&BlockPoints>,
+) {
+    let Ok(block_points) = blocks.get(trigger.block) else {
+        return;
+    };
+
+    let multiplier = combo.multiplier();
+    let gained = block_points.0 * multiplier;
+    score.points += gained;
+    if let Some(player) = &trigger.player {
+        *score.per_player.get_mut(player) += gained;
+    };
+
+    commands.trigger(ScoreChanged {
+        points: score.points,
+        player: trigger.player.clone(),
+        gained,
+        multiplier,
+    });
+}
+
+/// Runs before the hit breaks the block, so the breaking hit counts towards its multiplier.
+fn block_hit_combo_observer(
+    _: Trigger<BlockHitEvent>,
+    mut commands: Commands,
+    mut combo: ResMut<Combo>,
+) {
+    combo.hits += 1;
+    commands.trigger(ComboChanged {
+        hits: combo.hits,
+        multiplier: combo.multiplier(),
+    });
+}
+
+fn log_score_observer(trigger: Trigger<ScoreChanged>) {
+    match &trigger.player {
+        Some(player) => debug!(
+            "Score {} (+{} x{} for {:?})",
+            trigger.points, trigger.gained, trigger.multiplier, player
+        ),
+        None => debug!(
+            "Score {} (+{} x{})",
+            trigger.points, trigger.gained, trigger.multiplier
+        ),
+    };
+}
+
+fn log_combo_observer(trigger: Trigger<ComboChanged>) {
+    debug!("Combo {} hits (x{})", trigger.hits, trigger.multiplier);
+}
+
+fn reset_combo_on_paddle_system(
+    mut commands: Commands,
+    mut collision_started: EventReader<CollisionStarted>,
+    mut combo: ResMut<Combo>,
+    balls: Query<(), With<Ball>>,
+    players: Query<(), With<Player>>,
+) {
+    for &CollisionStarted(a, b) in collision_started.read() {
+        let touched_paddle = (balls.contains(a) && players.contains(b))
+            || (balls.contains(b) && players.contains(a));
+        if !touched_paddle || combo.hits == 0 {
+            continue;
+        };
+
+        debug!("Combo of {} hits ended", combo.hits);
+
+        *combo = Combo::default();
+        commands.trigger(ComboChanged {
+            hits: 0,
+            multiplier: combo.multiplier(),
+        });
+    }
+}
@@ -0,0 +1,142 @@
+//! Gameplay runs in `FixedUpdate` at [`TICK_RATE`] and only depends on the [`PlayerInputs`] of
+//! every step and the [`GameRng`]. The same seed and inputs always break the same blocks in the
+//! same order, which replays and regression tests rely on.
+//!
+//! Gameplay systems belong to a [`SimulationSet`]. The sets run in a fixed order, so systems of
+//! different plugins react to the same collisions and draw random numbers in the same order every
+//! time.
+//!
+//! [`PlayerInputs`]: crate::input::PlayerInputs
+
+use std::ops::{Deref, DerefMut};
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    StartGame,
+    app_state::{AppState, GameState},
+    world::campaign::Campaign,
+};
+
+/// Simulation steps per second.
+pub const TICK_RATE: f64 = 60.0;
+
+#[derive(Debug)]
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_hz(TICK_RATE))
+            .init_resource::<SimulationConfig>()
+            .init_resource::<GameRng>()
+            .init_resource::<SimulationTick>();
+
+        app.configure_sets(
+            FixedUpdate,
+            (
+                SimulationSet::Paddles,
+                SimulationSet::Balls,
+                SimulationSet::Blocks,
+                SimulationSet::PowerUps,
+            )
+                .chain()
+                .run_if(in_state(GameState::Running)),
+        );
+
+        app.add_observer(choose_seed_observer);
+
+        // Runs before anything that is spawned when the level starts.
+        app.add_systems(OnExit(AppState::LoadingLevel), reset_simulation_system)
+            .add_systems(
+                FixedLast,
+                count_tick_system.run_if(in_state(GameState::Running)),
+            );
+    }
+}
+
+/// The parts of a simulation step, in the order they run. They only run while
+/// [`GameState::Running`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum SimulationSet {
+    Paddles,
+    Balls,
+    Blocks,
+    PowerUps,
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct SimulationConfig {
+    /// The seed of every new game. Each game gets a random seed if this is `None`.
+    pub seed: Option<u64>,
+}
+
+/// The only source of randomness in gameplay. It is reseeded whenever a level starts, so every
+/// attempt at a level plays out the same for the same inputs.
+#[derive(Debug, Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+/// Simulation steps since the current level started.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct SimulationTick(pub u64);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            rng: ChaCha8Rng::seed_from_u64(0),
+        }
+    }
+}
+
+impl GameRng {
+    /// The seed of the current game.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts the random sequence of `level` of the campaign.
+    fn reset(&mut self, level: usize) {
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed.wrapping_add(level as u64));
+    }
+}
+
+impl Deref for GameRng {
+    type Target = ChaCha8Rng;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rng
+    }
+}
+
+impl DerefMut for GameRng {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rng
+    }
+}
+
+fn choose_seed_observer(
+    _: Trigger<StartGame>,
+    config: Res<SimulationConfig>,
+    mut rng: ResMut<GameRng>,
+) {
+    rng.seed = config.seed.unwrap_or_else(rand::random);
+    info!("Game seed is {}", rng.seed);
+}
+
+fn reset_simulation_system(
+    campaign: Res<Campaign>,
+    mut rng: ResMut<GameRng>,
+    mut tick: ResMut<SimulationTick>,
+) {
+    rng.reset(campaign.current());
+    *tick = SimulationTick::default();
+}
+
+fn count_tick_system(mut tick: ResMut<SimulationTick>) {
+    tick.0 += 1;
+}
@@ -0,0 +1,143 @@
+use bevy::{prelude::*, text::FontSmoothing};
+
+use crate::gamepad::GamepadFocus;
+
+/// Shared building blocks for the menus and screens.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_button_color_system);
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct ButtonColorScheme {
+    normal: Color,
+    hover: Color,
+    pressed: Color,
+}
+
+/// A full screen column that centers its children.
+pub fn screen() -> Node {
+    Node {
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        justify_content: JustifyContent::Center,
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(12.0),
+        ..Default::default()
+    }
+}
+
+/// A column that stacks buttons.
+pub fn button_column() -> Node {
+    Node {
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(12.0),
+        ..Default::default()
+    }
+}
+
+pub fn title(text: impl Into<String>) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: 64.0,
+            font_smoothing: FontSmoothing::AntiAliased,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+    )
+}
+
+pub fn label(text: impl Into<String>) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: 24.0,
+            font_smoothing: FontSmoothing::AntiAliased,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+    )
+}
+
+pub fn button(text: impl Into<String>) -> impl Bundle {
+    (
+        Button,
+        ButtonColorScheme::default(),
+        Node {
+            width: Val::Px(220.0),
+            height: Val::Px(60.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        BackgroundColor(ButtonColorScheme::default().normal),
+        children![(
+            Text::new(text),
+            TextFont {
+                font_size: 32.0,
+                font_smoothing: FontSmoothing::AntiAliased,
+                ..Default::default()
+            },
+            TextColor(Color::WHITE),
+        )],
+    )
+}
+
+/// Despawns every screen root marked with `T`.
+pub fn despawn_screen<T: Component>(mut commands: Commands, screens: Query<Entity, With<T>>) {
+    for screen in screens {
+        commands.entity(screen).despawn();
+    }
+}
+
+/// The button that the gamepad points at looks hovered.
+fn update_button_color_system(
+    mut buttons: Query<
+        (
+            Entity,
+            &Interaction,
+            Has<GamepadFocus>,
+            &mut BackgroundColor,
+            &ButtonColorScheme,
+        ),
+        With<Button>,
+    >,
+    changed: Query<(), Or<(Changed<Interaction>, Added<GamepadFocus>)>>,
+    mut unfocused: RemovedComponents<GamepadFocus>,
+) {
+    let unfocused = unfocused.read().collect::<Vec<_>>();
+
+    for (entity, interaction, focused, mut bg_color, btn_color_scheme) in buttons.iter_mut() {
+        if !changed.contains(entity) && !unfocused.contains(&entity) {
+            continue;
+        };
+
+        match (interaction, focused) {
+            (Interaction::None, false) => {
+                bg_color.0 = btn_color_scheme.normal;
+            }
+            (Interaction::None, true) | (Interaction::Hovered, _) => {
+                bg_color.0 = btn_color_scheme.hover;
+            }
+            (Interaction::Pressed, _) => {
+                bg_color.0 = btn_color_scheme.pressed;
+            }
+        };
+    }
+}
+
+impl Default for ButtonColorScheme {
+    fn default() -> Self {
+        Self {
+            normal: Color::linear_rgb(0.3, 0.3, 0.3),
+            hover: Color::linear_rgb(0.275, 0.275, 0.275),
+            pressed: Color::linear_rgb(0.2, 0.2, 0.2),
+        }
+    }
+}
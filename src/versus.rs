@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+
+use crate::{
+    RestartLevel, StopGame,
+    app_state::AppState,
+    game_mode::GameMode,
+    lives::Lives,
+    player::Player,
+    score::Score,
+    ui,
+    world::campaign::{Campaign, NextLevel},
+};
+
+/// The screen that compares both players after a versus round.
+#[derive(Debug)]
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::RoundOver), round_over_setup)
+            .add_systems(
+                OnExit(AppState::RoundOver),
+                ui::despawn_screen::<RoundOverScreen>,
+            )
+            .add_systems(
+                Update,
+                (
+                    rematch_button_pressed_system,
+                    next_round_button_pressed_system,
+                    menu_button_pressed_system,
+                )
+                    .run_if(in_state(AppState::RoundOver)),
+            );
+    }
+}
+
+#[derive(Debug, Component)]
+struct RoundOverScreen;
+
+#[derive(Debug, Component)]
+struct RematchButton;
+
+#[derive(Debug, Component)]
+struct NextRoundButton;
+
+#[derive(Debug, Component)]
+struct MenuButton;
+
+/// The winner of a round. A player who ran out of lives loses, otherwise the higher score wins.
+/// `None` is a draw.
+pub fn winner(score: &Score, lives: &Lives) -> Option<Player> {
+    let out_of_lives = |player: &Player| *lives.per_player.get(player) == 0;
+    match (out_of_lives(&Player::First), out_of_lives(&Player::Second)) {
+        (true, false) => return Some(Player::Second),
+        (false, true) => return Some(Player::First),
+        _ => (),
+    };
+
+    let (first, second) = (score.per_player.first, score.per_player.second);
+    match first.cmp(&second) {
+        std::cmp::Ordering::Greater => Some(Player::First),
+        std::cmp::Ordering::Less => Some(Player::Second),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+fn round_over_setup(
+    mut commands: Commands,
+    campaign: Res<Campaign>,
+    game_mode: Res<GameMode>,
+    score: Res<Score>,
+    lives: Res<Lives>,
+) {
+    let title = match winner(&score, &lives) {
+        Some(player) => format!("{} WINS", player.short_name()),
+        None => "DRAW".to_string(),
+    };
+    // Another round only makes sense if both players can still play it.
+    let can_continue = !campaign.is_last_level()
+        && game_mode
+            .players()
+            .iter()
+            .all(|player| lives.of(*game_mode, Some(player)) > 0);
+
+    let screen = commands
+        .spawn((
+            RoundOverScreen,
+            ui::screen(),
+            children![
+                ui::title(title),
+                ui::label(format!("Round {}", campaign.current() + 1)),
+                ui::label(player_result_text(&Player::First, &score, &lives)),
+                ui::label(player_result_text(&Player::Second, &score, &lives)),
+            ],
+        ))
+        .id();
+    let buttons = commands.spawn((ChildOf(screen), ui::button_column())).id();
+
+    if can_continue {
+        commands.spawn((ChildOf(buttons), ui::button("Next Round"), NextRoundButton));
+    };
+    commands.spawn((ChildOf(buttons), ui::button("Rematch"), RematchButton));
+    commands.spawn((ChildOf(buttons), ui::button("Main Menu"), MenuButton));
+}
+
+fn player_result_text(player: &Player, score: &Score, lives: &Lives) -> String {
+    format!(
+        "{}: {} points, {} lives left",
+        player.short_name(),
+        score.per_player.get(player),
+        lives.per_player.get(player)
+    )
+}
+
+fn rematch_button_pressed_system(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<RematchButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(RestartLevel);
+        };
+    }
+}
+
+fn next_round_button_pressed_system(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<NextRoundButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(NextLevel);
+        };
+    }
+}
+
+fn menu_button_pressed_system(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>, With<MenuButton>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(StopGame);
+        };
+    }
+}
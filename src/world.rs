@@ -1,32 +1,73 @@
 use avian2d::prelude::{Collider, Friction, Restitution, RigidBody};
-use bevy::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
 
-use crate::{app_state::AppState, world::block::BlockPlugin};
+use crate::{
+    app_state::AppState,
+    game_mode::GameMode,
+    world::{block::BlockPlugin, campaign::CampaignPlugin, level::LevelPlugin},
+};
 
-mod block;
+pub mod block;
+pub mod campaign;
+pub mod level;
+
+/// The size of Bevy's default window.
+const DEFAULT_PLAYFIELD_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
 
 #[derive(Debug)]
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(BlockPlugin);
+        app.add_plugins((LevelPlugin, CampaignPlugin, BlockPlugin));
+
+        app.init_resource::<Playfield>();
 
-        app.add_systems(OnEnter(AppState::InGame), load_level_system)
-            .add_systems(OnEnter(AppState::MainMenu), unload_level_system);
+        // The startup systems size the paddles from the playfield.
+        app.add_systems(PreStartup, follow_window_system)
+            .add_systems(First, follow_window_system)
+            .add_systems(OnEnter(AppState::InGame), load_level_system)
+            .add_systems(OnExit(AppState::InGame), unload_level_system);
     }
 }
 
+/// The field the game is played on, centered on the origin. It has the size of the primary
+/// window, or keeps its size if there is none, e.g. in [`crate::headless`] apps.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct Playfield {
+    pub size: Vec2,
+}
+
 #[derive(Debug, Component)]
 pub struct Border;
 
+/// The border above the blocks. Balls speed up when they touch it. There is none in
+/// [`GameMode::SplitField`], where the second player defends the top edge.
+#[derive(Debug, Component)]
+pub struct TopBorder;
+
+impl Default for Playfield {
+    fn default() -> Self {
+        Self {
+            size: DEFAULT_PLAYFIELD_SIZE,
+        }
+    }
+}
+
+impl Playfield {
+    pub fn half_size(&self) -> Vec2 {
+        self.size / 2.0
+    }
+}
+
 fn load_level_system(
     mut commands: Commands,
-    windows: Query<&Window>,
+    game_mode: Res<GameMode>,
+    playfield: Res<Playfield>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-) -> Result {
-    let size = windows.single()?.size();
+) {
+    let size = playfield.size;
 
     let border_material = materials.add(Color::linear_rgb(1.0, 1.0, 1.0));
 
@@ -35,10 +76,10 @@ fn load_level_system(
     let side_mesh = meshes.add(Rectangle::new(5.0, size.y));
     let side_collider = Collider::rectangle(5.0, size.y);
 
-    commands.spawn_batch([
-        // Top
-        (
+    if !game_mode.is_split() {
+        commands.spawn((
             Border,
+            TopBorder,
             Mesh2d(top_mesh),
             MeshMaterial2d(border_material.clone()),
             Transform::from_xyz(0.0, size.y / 2.0, 0.0),
@@ -46,7 +87,10 @@ fn load_level_system(
             top_collider,
             Restitution::new(1.0),
             Friction::new(0.0),
-        ),
+        ));
+    };
+
+    commands.spawn_batch([
         // Left
         (
             Border,
@@ -72,8 +116,17 @@ fn load_level_system(
     ]);
 
     info!("Level loaded");
+}
 
-    Ok(())
+fn follow_window_system(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut playfield: ResMut<Playfield>,
+) {
+    if let Ok(window) = windows.single() {
+        playfield.set_if_neq(Playfield {
+            size: window.size(),
+        });
+    };
 }
 
 fn unload_level_system(mut commands: Commands, borders: Query<Entity, With<Border>>) {
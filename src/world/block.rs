@@ -1,28 +1,44 @@
 use avian2d::prelude::{
     Collider, CollisionEventsEnabled, CollisionStarted, Friction, Restitution, RigidBody,
 };
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::{audio::Volume, prelude::*};
 
-use crate::{StopGame, app_state::AppState, ball::Ball};
+use crate::{
+    app_state::AppState,
+    ball::{Ball, LastTouched},
+    game_mode::GameMode,
+    player::Player,
+    simulation::SimulationSet,
+    world::{
+        Playfield,
+        campaign::LevelCleared,
+        level::{CurrentLevel, Level, damage_color},
+    },
+};
 
-const ROWS: usize = 18;
-const COLUMNS: usize = 12;
+/// The highest hit count that has its own damage color.
+const MAX_HITS: u32 = 9;
+/// Explosions break every breakable block within this many cells.
+const EXPLOSION_RADIUS: f32 = 1.5;
 
 #[derive(Debug)]
 pub struct BlockPlugin;
 
 impl Plugin for BlockPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<BlockBreakEvent>();
+        app.add_event::<BlockHitEvent>()
+            .add_event::<BlockBreakEvent>();
 
-        app.add_observer(despawn_block_observer);
+        app.add_observer(damage_block_observer)
+            .add_observer(despawn_block_observer)
+            .add_observer(explode_block_observer);
 
         app.add_systems(OnEnter(AppState::InGame), load_blocks_system)
-            .add_systems(OnEnter(AppState::MainMenu), unload_blocks_system)
+            .add_systems(OnExit(AppState::InGame), unload_blocks_system)
             .add_systems(
                 FixedUpdate,
-                (trigger_ball_break_event_system, check_for_win_system)
-                    .run_if(in_state(AppState::InGame)),
+                (trigger_block_hit_event_system, check_for_win_system)
+                    .in_set(SimulationSet::Blocks),
             );
     }
 }
@@ -30,45 +46,164 @@ impl Plugin for BlockPlugin {
 #[derive(Debug, Component)]
 pub struct Block;
 
+/// Decides how a block reacts to hits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub enum BlockKind {
+    Normal,
+    /// Never breaks and does not count towards clearing the level.
+    Indestructible,
+    /// Breaks every block within [`EXPLOSION_RADIUS`] when broken.
+    Explosive,
+    /// Ignores hits that do not break steel.
+    Steel,
+}
+
+/// Points awarded for breaking a block.
+#[derive(Debug, Component)]
+pub struct BlockPoints(pub u32);
+
+/// Remaining hits before a block breaks.
 #[derive(Debug, Component)]
-pub struct BlockBreakSound(Handle<AudioSource>);
+pub struct Health(pub u32);
+
+/// One material per remaining hit count, used to show the damage of multi hit blocks.
+#[derive(Debug, Resource)]
+struct DamageMaterials(Vec<Handle<ColorMaterial>>);
+
+/// The distance between the centers of two neighbouring blocks.
+#[derive(Debug, Resource)]
+struct BlockCellSize(Vec2);
+
+/// The size of every block of the current level.
+#[derive(Debug, Resource)]
+pub struct BlockSize(pub Vec2);
+
+#[derive(Debug, Resource)]
+struct BlockSound(Handle<AudioSource>);
+
+/// Triggered when a block is hit. Takes one hit point and breaks the block at zero.
+#[derive(Debug, Event)]
+pub struct BlockHitEvent {
+    pub block: Entity,
+    /// Whether the hit can damage [`BlockKind::Steel`] blocks.
+    pub breaks_steel: bool,
+    /// The player the hit is credited to, if any.
+    pub player: Option<Player>,
+}
 
 #[derive(Debug, Event)]
-pub struct BlockBreakEvent(Entity);
+pub struct BlockBreakEvent {
+    pub block: Entity,
+    /// The player whose ball, laser or explosion broke the block, if any.
+    pub player: Option<Player>,
+}
+
+impl BlockKind {
+    pub fn is_breakable(&self) -> bool {
+        *self != Self::Indestructible
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Normal => Color::hsv(319.0, 0.95, 0.9),
+            Self::Indestructible => Color::hsv(0.0, 0.0, 0.35),
+            Self::Explosive => Color::hsv(15.0, 1.0, 1.0),
+            Self::Steel => Color::hsv(210.0, 0.15, 0.75),
+        }
+    }
+
+    /// The sound played when a hit does not break the block.
+    fn hit_sound(&self) -> PlaybackSettings {
+        match self {
+            Self::Normal | Self::Explosive => PlaybackSettings::DESPAWN
+                .with_speed(1.5)
+                .with_volume(Volume::Linear(0.5)),
+            Self::Indestructible | Self::Steel => PlaybackSettings::DESPAWN
+                .with_speed(2.2)
+                .with_volume(Volume::Linear(0.35)),
+        }
+    }
+
+    fn break_sound(&self) -> PlaybackSettings {
+        match self {
+            Self::Normal | Self::Indestructible => PlaybackSettings::DESPAWN,
+            Self::Explosive => PlaybackSettings::DESPAWN.with_speed(0.55),
+            Self::Steel => PlaybackSettings::DESPAWN.with_speed(0.8),
+        }
+    }
+}
 
 fn load_blocks_system(
     mut commands: Commands,
-    windows: Query<&Window, With<PrimaryWindow>>,
+    playfield: Res<Playfield>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     asset_server: Res<AssetServer>,
+    game_mode: Res<GameMode>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
 ) -> Result {
-    let size = windows.single()?.size();
+    let size = playfield.size;
+    let level = levels
+        .get(&current_level.0)
+        .ok_or("The current level is not loaded")?;
+    let rows = if game_mode.is_split() {
+        level.mirrored_rows()
+    } else {
+        level.rows.clone()
+    };
 
     let block_space = 5.0;
-    let rect_width = size.x / COLUMNS as f32 - block_space;
+    let rect_width = size.x / level.columns as f32 - block_space;
     let rect_height = 20.0;
     let mesh_handle = meshes.add(Rectangle::new(rect_width, rect_height));
-    let material_handle = materials.add(Color::hsv(319.0, 0.95, 0.9));
-    let block_break_sound_handle = asset_server.load("block_break.wav");
+    let mut material_handles = Vec::<(Color, Handle<ColorMaterial>)>::new();
+    let damage_materials = (1..=MAX_HITS)
+        .map(|hits| materials.add(damage_color(hits)))
+        .collect();
 
-    let origin = Vec2::new(-size.x, size.y) * 0.5
+    // The wall hangs from the top edge, or sits in the middle of a split field.
+    let top = if game_mode.is_split() {
+        rows.len() as f32 * (rect_height + block_space) / 2.0
+    } else {
+        size.y / 2.0
+    };
+    let origin = Vec2::new(-size.x / 2.0, top)
         + Vec2::new(
             (rect_width + block_space) / 2.0,
             -(rect_height + block_space) / 2.0,
         );
 
-    let mut blocks = Vec::with_capacity(ROWS * COLUMNS);
-    for row in 0..ROWS {
-        for column in 0..COLUMNS {
+    let mut blocks = Vec::with_capacity(rows.len() * level.columns);
+    for (row, cells) in rows.iter().enumerate() {
+        for (column, cell) in cells.iter().enumerate() {
+            let Some(level_block) = cell else {
+                continue;
+            };
+
             let x = origin.x + column as f32 * (rect_width + block_space);
             let y = origin.y - row as f32 * (rect_height + block_space);
 
+            // Share one material between all blocks of the same color.
+            let material_handle = match material_handles
+                .iter()
+                .find(|(color, _)| *color == level_block.color)
+            {
+                Some((_, handle)) => handle.clone(),
+                None => {
+                    let handle = materials.add(level_block.color);
+                    material_handles.push((level_block.color, handle.clone()));
+                    handle
+                }
+            };
+
             blocks.push((
                 Block,
-                BlockBreakSound(block_break_sound_handle.clone()),
+                level_block.kind,
+                Health(level_block.hits),
+                BlockPoints(level_block.points),
                 Mesh2d(mesh_handle.clone()),
-                MeshMaterial2d(material_handle.clone()),
+                MeshMaterial2d(material_handle),
                 Transform::from_xyz(x, y, 0.0),
                 RigidBody::Static,
                 Collider::rectangle(rect_width, rect_height),
@@ -80,6 +215,15 @@ fn load_blocks_system(
     }
 
     commands.spawn_batch(blocks);
+    commands.insert_resource(DamageMaterials(damage_materials));
+    commands.insert_resource(BlockCellSize(Vec2::new(
+        rect_width + block_space,
+        rect_height + block_space,
+    )));
+    commands.insert_resource(BlockSize(Vec2::new(rect_width, rect_height)));
+    commands.insert_resource(BlockSound(asset_server.load("block_break.wav")));
+
+    info!("Spawned blocks of level {}", level.name);
 
     Ok(())
 }
@@ -92,48 +236,130 @@ fn unload_blocks_system(mut commands: Commands, blocks: Query<Entity, With<Block
     info!("Blocks unloaded")
 }
 
-fn trigger_ball_break_event_system(
+fn trigger_block_hit_event_system(
     mut commands: Commands,
     mut collision_started: EventReader<CollisionStarted>,
-    balls: Query<(), With<Ball>>,
+    balls: Query<Option<&LastTouched>, With<Ball>>,
     blocks: Query<(), With<Block>>,
 ) {
     for &CollisionStarted(a, b) in collision_started.read() {
-        let block = match (
+        let (ball, block) = match (
             balls.contains(a) && blocks.contains(b),
             balls.contains(b) && blocks.contains(a),
         ) {
-            (true, _) => b,
-            (_, true) => a,
-            _ => return,
+            (true, _) => (a, b),
+            (_, true) => (b, a),
+            _ => continue,
         };
 
         debug!("Ball touched {}", block);
 
-        commands.trigger(BlockBreakEvent(block));
+        let player = balls
+            .get(ball)
+            .ok()
+            .flatten()
+            .map(|last_touched| last_touched.0.clone());
+        commands.trigger(BlockHitEvent {
+            block,
+            breaks_steel: false,
+            player,
+        });
     }
 }
 
+fn damage_block_observer(
+    trigger: Trigger<BlockHitEvent>,
+    mut commands: Commands,
+    damage_materials: Res<DamageMaterials>,
+    block_sound: Res<BlockSound>,
+    mut blocks: Query<(&BlockKind, &mut Health, &mut MeshMaterial2d<ColorMaterial>)>,
+) {
+    let block = trigger.block;
+    let Ok((kind, mut health, mut material)) = blocks.get_mut(block) else {
+        return;
+    };
+
+    // The block was already broken by an earlier hit in the same tick.
+    if health.0 == 0 {
+        return;
+    };
+
+    let resists = match kind {
+        BlockKind::Indestructible => true,
+        BlockKind::Steel => !trigger.breaks_steel,
+        BlockKind::Normal | BlockKind::Explosive => false,
+    };
+
+    if !resists {
+        health.0 -= 1;
+        if health.0 == 0 {
+            commands.trigger(BlockBreakEvent {
+                block,
+                player: trigger.player.clone(),
+            });
+            return;
+        };
+
+        if let Some(handle) = damage_materials.0.get(health.0.min(MAX_HITS) as usize - 1) {
+            material.0 = handle.clone();
+        };
+    };
+
+    commands.spawn((AudioPlayer::new(block_sound.0.clone()), kind.hit_sound()));
+
+    debug!("Block {} has {} hits left", block, health.0);
+}
+
 fn despawn_block_observer(
     trigger: Trigger<BlockBreakEvent>,
     mut commands: Commands,
-    blocks: Query<&BlockBreakSound, With<Block>>,
+    block_sound: Res<BlockSound>,
+    blocks: Query<&BlockKind, With<Block>>,
 ) -> Result {
-    let block_break_sound_handle = blocks.get(trigger.0)?;
-    commands.spawn((
-        AudioPlayer::new(block_break_sound_handle.0.clone()),
-        PlaybackSettings::DESPAWN,
-    ));
+    let kind = blocks.get(trigger.block)?;
+    commands.spawn((AudioPlayer::new(block_sound.0.clone()), kind.break_sound()));
 
-    commands.entity(trigger.0).despawn();
-    debug!("Despawned block {}", trigger.0);
+    commands.entity(trigger.block).despawn();
+    debug!("Despawned block {}", trigger.block);
 
     Ok(())
 }
 
-fn check_for_win_system(mut commands: Commands, blocks: Query<(), With<Block>>) {
-    if blocks.is_empty() {
-        info!("Won");
-        commands.trigger(StopGame);
+fn explode_block_observer(
+    trigger: Trigger<BlockBreakEvent>,
+    mut commands: Commands,
+    cell_size: Res<BlockCellSize>,
+    mut blocks: Query<(Entity, &BlockKind, &mut Health, &Transform), With<Block>>,
+) {
+    let Ok((_, BlockKind::Explosive, _, transform)) = blocks.get(trigger.block) else {
+        return;
+    };
+    let center = transform.translation.truncate();
+
+    for (block, kind, mut health, transform) in blocks.iter_mut() {
+        // Measured in cells so the explosion covers the same blocks regardless of the block size.
+        let distance = (transform.translation.truncate() - center) / cell_size.0;
+        if block == trigger.block
+            || !kind.is_breakable()
+            || health.0 == 0
+            || distance.length() > EXPLOSION_RADIUS
+        {
+            continue;
+        };
+
+        // Zero health marks the block as broken so chained explosions do not break it twice.
+        health.0 = 0;
+        commands.trigger(BlockBreakEvent {
+            block,
+            player: trigger.player.clone(),
+        });
+    }
+
+    debug!("Block {} exploded", trigger.block);
+}
+
+fn check_for_win_system(mut commands: Commands, blocks: Query<&BlockKind, With<Block>>) {
+    if !blocks.iter().any(BlockKind::is_breakable) {
+        commands.trigger(LevelCleared);
     };
 }
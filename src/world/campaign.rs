@@ -0,0 +1,190 @@
+//! The campaign plays every level in `assets/levels` ordered by file name.
+
+use std::time::Duration;
+
+use bevy::{
+    asset::{LoadedFolder, RecursiveDependencyLoadState},
+    prelude::*,
+};
+
+use crate::{
+    StartGame,
+    app_state::AppState,
+    game_mode::GameMode,
+    simulation::SimulationSet,
+    world::{
+        block::BlockBreakEvent,
+        level::{CurrentLevel, Level},
+    },
+};
+
+const LEVELS_FOLDER: &str = "levels";
+
+#[derive(Debug)]
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelStats>();
+
+        app.add_observer(start_campaign_observer)
+            .add_observer(next_level_observer)
+            .add_observer(level_cleared_observer)
+            .add_observer(count_broken_blocks_observer);
+
+        app.add_systems(Startup, setup)
+            .add_systems(OnEnter(AppState::InGame), reset_level_stats_system)
+            .add_systems(
+                Update,
+                wait_for_levels_system.run_if(in_state(AppState::LoadingLevel)),
+            )
+            .add_systems(
+                FixedUpdate,
+                level_timer_system.in_set(SimulationSet::Blocks),
+            );
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct Campaign {
+    folder: Handle<LoadedFolder>,
+    /// Ordered by file name. Empty until the levels folder is loaded.
+    levels: Vec<Handle<Level>>,
+    current: usize,
+}
+
+/// Statistics of the level that is currently played or was just completed.
+#[derive(Debug, Default, Resource)]
+pub struct LevelStats {
+    pub elapsed: Duration,
+    pub blocks_broken: u32,
+}
+
+/// Triggered when the last block of the current level was broken.
+#[derive(Debug, Event)]
+pub struct LevelCleared;
+
+/// Loads the level after the current one.
+#[derive(Debug, Event)]
+pub struct NextLevel;
+
+impl Campaign {
+    /// Zero based index of the current level.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Makes `level` the next level to load. Replays start at the level they recorded.
+    pub fn select(&mut self, level: usize) {
+        self.current = level;
+    }
+
+    pub fn is_last_level(&self) -> bool {
+        self.current + 1 >= self.levels.len()
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Campaign {
+        folder: asset_server.load_folder(LEVELS_FOLDER),
+        levels: Vec::new(),
+        current: 0,
+    });
+}
+
+fn start_campaign_observer(_: Trigger<StartGame>, mut campaign: ResMut<Campaign>) {
+    campaign.current = 0;
+}
+
+fn next_level_observer(
+    _: Trigger<NextLevel>,
+    mut campaign: ResMut<Campaign>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if campaign.is_last_level() {
+        warn!("There is no level after the last level");
+        return;
+    };
+
+    campaign.current += 1;
+    app_state.set(AppState::LoadingLevel);
+}
+
+fn level_cleared_observer(
+    _: Trigger<LevelCleared>,
+    campaign: Res<Campaign>,
+    game_mode: Res<GameMode>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if game_mode.is_competitive() {
+        info!("Round {} over", campaign.current + 1);
+        app_state.set(AppState::RoundOver);
+    } else if campaign.is_last_level() {
+        info!("Campaign won");
+        app_state.set(AppState::CampaignWon);
+    } else {
+        info!("Level {} complete", campaign.current + 1);
+        app_state.set(AppState::LevelComplete);
+    };
+}
+
+fn wait_for_levels_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    folders: Res<Assets<LoadedFolder>>,
+    mut campaign: ResMut<Campaign>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    match asset_server.recursive_dependency_load_state(&campaign.folder) {
+        RecursiveDependencyLoadState::Loaded => (),
+        RecursiveDependencyLoadState::Failed(err) => {
+            error!("Could not load levels: {err}");
+            app_state.set(AppState::MainMenu);
+            return;
+        }
+        RecursiveDependencyLoadState::NotLoaded | RecursiveDependencyLoadState::Loading => return,
+    };
+
+    if campaign.levels.is_empty() {
+        let Some(folder) = folders.get(&campaign.folder) else {
+            return;
+        };
+
+        let mut levels = folder
+            .handles
+            .iter()
+            .filter_map(|handle| handle.clone().try_typed::<Level>().ok())
+            .collect::<Vec<_>>();
+        levels.sort_by_cached_key(|level| level.path().map(|path| path.path().to_owned()));
+
+        campaign.levels = levels;
+    };
+
+    let Some(level) = campaign.levels.get(campaign.current) else {
+        error!(
+            "There is no level {} in {LEVELS_FOLDER}",
+            campaign.current + 1
+        );
+        app_state.set(AppState::MainMenu);
+        return;
+    };
+
+    commands.insert_resource(CurrentLevel(level.clone()));
+    app_state.set(AppState::InGame);
+}
+
+fn reset_level_stats_system(mut level_stats: ResMut<LevelStats>) {
+    *level_stats = LevelStats::default();
+}
+
+fn level_timer_system(time: Res<Time>, mut level_stats: ResMut<LevelStats>) {
+    level_stats.elapsed += time.delta();
+}
+
+fn count_broken_blocks_observer(_: Trigger<BlockBreakEvent>, mut level_stats: ResMut<LevelStats>) {
+    level_stats.blocks_broken += 1;
+}
@@ -0,0 +1,481 @@
+//! Level files.
+//!
+//! A level file consists of a header with `key: value` pairs, a `---` separator and an ASCII grid
+//! where every character describes one block cell. Lines starting with `;` are comments.
+//!
+//! ```text
+//! ; The classic wall.
+//! name: Classic
+//! ---
+//! rrrrrrrrrrrr
+//! oooooooooooo
+//! ..yyyyyyyy..
+//! ```
+//!
+//! Header keys:
+//! - `name`: shown in the HUD and on the level complete screen (required)
+//! - `drop_chance`: chance between 0 and 1 that a broken block drops a power-up (default 0.1)
+//! - `power_ups`: comma separated names of the power-ups that can drop (default all)
+//! - `ball_speed`: speed a new ball starts with (default 500)
+//! - `max_ball_speed`: the ball speeds up during play until it reaches this (default 850)
+//!
+//! Grid characters:
+//! - `.` or ` `: empty cell
+//! - `r`, `o`, `y`, `g`, `c`, `b`, `p`, `w`: block colored red (7 points), orange (5), yellow (1),
+//!   green (3), cyan (3), blue (4), pink (5) or white (1)
+//! - `2` to `9`: block that needs that many hits to break, worth 3 points per hit
+//! - `#`: indestructible wall that does not need to be broken to clear the level
+//! - `*`: explosive block that breaks its neighbours (5 points)
+//! - `S`: steel block that only breaks from explosions and power-ups (10 points)
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use thiserror::Error;
+
+use crate::world::block::BlockKind;
+
+const DEFAULT_DROP_CHANCE: f32 = 0.1;
+pub const DEFAULT_BALL_SPEED: f32 = 500.0;
+pub const DEFAULT_MAX_BALL_SPEED: f32 = 850.0;
+
+const COMMENT: char = ';';
+const SEPARATOR: &str = "---";
+
+#[derive(Debug)]
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>().init_asset_loader::<LevelLoader>();
+    }
+}
+
+/// The level that gets spawned when entering [`AppState::InGame`].
+#[derive(Debug, Resource)]
+pub struct CurrentLevel(pub Handle<Level>);
+
+#[derive(Debug, Clone, Asset, TypePath)]
+pub struct Level {
+    pub name: String,
+    /// The grid from top to bottom. Every row has exactly `columns` cells.
+    pub rows: Vec<Vec<Option<LevelBlock>>>,
+    pub columns: usize,
+    /// The chance that a broken block drops a power-up.
+    pub drop_chance: f32,
+    /// Names of the power-ups that can drop. `None` allows every registered power-up.
+    pub power_ups: Option<Vec<String>>,
+    pub ball_speed: f32,
+    pub max_ball_speed: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelBlock {
+    pub color: Color,
+    /// Base points for breaking the block, before the combo multiplier is applied.
+    pub points: u32,
+    /// How many hits the block takes before it breaks.
+    pub hits: u32,
+    pub kind: BlockKind,
+}
+
+#[derive(Debug, Default)]
+pub struct LevelLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("Could not read level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Level file is not valid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("{line}:{column}: {kind}")]
+    Parse {
+        line: usize,
+        column: usize,
+        kind: LevelParseErrorKind,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum LevelParseErrorKind {
+    #[error("Expected `key: value`")]
+    MalformedHeader,
+    #[error("Unknown header key `{0}`")]
+    UnknownKey(String),
+    #[error("Duplicate header key `{0}`")]
+    DuplicateKey(String),
+    #[error("Invalid value `{value}` for `{key}`")]
+    InvalidValue { key: String, value: String },
+    #[error("Unknown block character `{0}`")]
+    UnknownBlock(char),
+    #[error("Missing header key `{0}`")]
+    MissingKey(&'static str),
+    #[error("`max_ball_speed` is lower than `ball_speed`")]
+    MaxBallSpeedTooLow,
+    #[error("Missing `{SEPARATOR}` separator before the grid")]
+    MissingSeparator,
+    #[error("The grid does not contain any rows")]
+    EmptyGrid,
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Level, LevelLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        parse_level(std::str::from_utf8(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level"]
+    }
+}
+
+impl Level {
+    /// The grid followed by its upside down copy, so both halves of the field face the same wall.
+    pub fn mirrored_rows(&self) -> Vec<Vec<Option<LevelBlock>>> {
+        self.rows
+            .iter()
+            .chain(self.rows.iter().rev())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Parses the contents of a level file. Line and column numbers in errors start at 1.
+pub fn parse_level(source: &str) -> Result<Level, LevelLoaderError> {
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line));
+
+    let mut name = None;
+    let mut drop_chance = None;
+    let mut power_ups = None;
+    let mut ball_speed = None;
+    // The position of the value, to point at it if it turns out lower than `ball_speed`.
+    let mut max_ball_speed = None;
+    let mut seen_keys = Vec::new();
+    let mut separator_line = None;
+    for (line_number, line) in lines.by_ref() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with(COMMENT) {
+            continue;
+        };
+
+        if trimmed == SEPARATOR {
+            separator_line = Some(line_number);
+            break;
+        };
+
+        let error = |column: usize, kind| LevelLoaderError::Parse {
+            line: line_number,
+            column,
+            kind,
+        };
+        // Columns count characters, not bytes.
+        let key_column = leading_whitespace(line) + 1;
+
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(error(key_column, LevelParseErrorKind::MalformedHeader));
+        };
+        let value_column = key.chars().count() + 1 + leading_whitespace(value) + 1;
+        let (key, value) = (key.trim(), value.trim());
+
+        if seen_keys.contains(&key) {
+            return Err(error(
+                key_column,
+                LevelParseErrorKind::DuplicateKey(key.to_string()),
+            ));
+        };
+        seen_keys.push(key);
+
+        let invalid_value = || {
+            error(
+                value_column,
+                LevelParseErrorKind::InvalidValue {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                },
+            )
+        };
+
+        match key {
+            "name" => {
+                if value.is_empty() {
+                    return Err(invalid_value());
+                };
+
+                name = Some(value.to_string());
+            }
+            "drop_chance" => {
+                let chance = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|chance| (0.0..=1.0).contains(chance))
+                    .ok_or_else(invalid_value)?;
+
+                drop_chance = Some(chance);
+            }
+            "power_ups" => {
+                power_ups = Some(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|power_up| !power_up.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                );
+            }
+            "ball_speed" | "max_ball_speed" => {
+                let speed = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|speed| speed.is_finite() && *speed > 0.0)
+                    .ok_or_else(invalid_value)?;
+
+                match key {
+                    "ball_speed" => ball_speed = Some(speed),
+                    _ => max_ball_speed = Some((speed, line_number, value_column)),
+                };
+            }
+            _ => {
+                return Err(error(
+                    key_column,
+                    LevelParseErrorKind::UnknownKey(key.to_string()),
+                ));
+            }
+        };
+    }
+
+    let Some(separator_line) = separator_line else {
+        return Err(LevelLoaderError::Parse {
+            line: source.lines().count().max(1),
+            column: 1,
+            kind: LevelParseErrorKind::MissingSeparator,
+        });
+    };
+
+    let mut rows = Vec::new();
+    let mut last_line = 0;
+    for (line_number, line) in lines {
+        last_line = line_number;
+        if line.trim_start().starts_with(COMMENT) {
+            continue;
+        };
+
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(index, character)| {
+                parse_block(character).map_err(|kind| LevelLoaderError::Parse {
+                    line: line_number,
+                    column: index + 1,
+                    kind,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.push(row);
+    }
+
+    // Trailing empty lines are not part of the grid.
+    while rows
+        .last()
+        .is_some_and(|row| row.iter().all(Option::is_none))
+    {
+        rows.pop();
+    }
+
+    if rows.is_empty() {
+        return Err(LevelLoaderError::Parse {
+            line: last_line.max(1),
+            column: 1,
+            kind: LevelParseErrorKind::EmptyGrid,
+        });
+    };
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+    for row in rows.iter_mut() {
+        row.resize(columns, None);
+    }
+
+    // The header ends at the separator, so that is where missing keys are reported.
+    let Some(name) = name else {
+        return Err(LevelLoaderError::Parse {
+            line: separator_line,
+            column: 1,
+            kind: LevelParseErrorKind::MissingKey("name"),
+        });
+    };
+
+    let ball_speed = ball_speed.unwrap_or(DEFAULT_BALL_SPEED);
+    let max_ball_speed = match max_ball_speed {
+        Some((speed, line, column)) if speed < ball_speed => {
+            return Err(LevelLoaderError::Parse {
+                line,
+                column,
+                kind: LevelParseErrorKind::MaxBallSpeedTooLow,
+            });
+        }
+        Some((speed, ..)) => speed,
+        None => DEFAULT_MAX_BALL_SPEED.max(ball_speed),
+    };
+
+    Ok(Level {
+        name,
+        rows,
+        columns,
+        drop_chance: drop_chance.unwrap_or(DEFAULT_DROP_CHANCE),
+        power_ups,
+        ball_speed,
+        max_ball_speed,
+    })
+}
+
+fn leading_whitespace(text: &str) -> usize {
+    text.chars()
+        .take_while(|character| character.is_whitespace())
+        .count()
+}
+
+fn parse_block(character: char) -> Result<Option<LevelBlock>, LevelParseErrorKind> {
+    let (color, points) = match character {
+        '.' | ' ' => return Ok(None),
+        '2'..='9' => {
+            let hits = character.to_digit(10).unwrap_or(1);
+            return Ok(Some(LevelBlock {
+                color: damage_color(hits),
+                points: hits * 3,
+                hits,
+                kind: BlockKind::Normal,
+            }));
+        }
+        '#' => return Ok(Some(special_block(BlockKind::Indestructible, 0))),
+        '*' => return Ok(Some(special_block(BlockKind::Explosive, 5))),
+        'S' => return Ok(Some(special_block(BlockKind::Steel, 10))),
+        'r' => (Color::hsv(0.0, 0.85, 0.9), 7),
+        'o' => (Color::hsv(30.0, 0.9, 0.95), 5),
+        'y' => (Color::hsv(55.0, 0.9, 0.95), 1),
+        'g' => (Color::hsv(120.0, 0.8, 0.8), 3),
+        'c' => (Color::hsv(185.0, 0.85, 0.9), 3),
+        'b' => (Color::hsv(225.0, 0.85, 0.9), 4),
+        'p' => (Color::hsv(319.0, 0.95, 0.9), 5),
+        'w' => (Color::hsv(0.0, 0.0, 0.9), 1),
+        _ => return Err(LevelParseErrorKind::UnknownBlock(character)),
+    };
+
+    Ok(Some(LevelBlock {
+        color,
+        points,
+        hits: 1,
+        kind: BlockKind::Normal,
+    }))
+}
+
+fn special_block(kind: BlockKind, points: u32) -> LevelBlock {
+    LevelBlock {
+        color: kind.color(),
+        points,
+        hits: 1,
+        kind,
+    }
+}
+
+/// The color of a block with `hits` remaining hits. Clamped to the range 1 to 9.
+pub fn damage_color(hits: u32) -> Color {
+    const HUES: [f32; 9] = [55.0, 30.0, 0.0, 330.0, 290.0, 250.0, 220.0, 190.0, 160.0];
+
+    Color::hsv(HUES[hits.clamp(1, 9) as usize - 1], 0.85, 0.9)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(source: &str) -> (usize, usize, LevelParseErrorKind) {
+        match parse_level(source) {
+            Err(LevelLoaderError::Parse { line, column, kind }) => (line, column, kind),
+            other => panic!("Expected a parse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_valid_level() {
+        let level = parse_level(
+            "; A comment.\nname: Test\nball_speed: 400\npower_ups: laser, multi_ball\n---\nr.2\n#*\n\n",
+        )
+        .unwrap();
+
+        assert_eq!(level.name, "Test");
+        assert_eq!(level.columns, 3);
+        assert_eq!(level.rows.len(), 2);
+        assert_eq!(level.rows[0][0].map(|block| block.points), Some(7));
+        assert_eq!(level.rows[0][1], None);
+        assert_eq!(level.rows[0][2].map(|block| block.hits), Some(2));
+        assert_eq!(
+            level.rows[1][1].map(|block| block.kind),
+            Some(BlockKind::Explosive)
+        );
+        assert_eq!(level.rows[1][2], None);
+        assert_eq!(level.ball_speed, 400.0);
+        assert_eq!(level.max_ball_speed, DEFAULT_MAX_BALL_SPEED);
+        assert_eq!(level.drop_chance, DEFAULT_DROP_CHANCE);
+        assert_eq!(
+            level.power_ups,
+            Some(vec!["laser".to_string(), "multi_ball".to_string()])
+        );
+    }
+
+    #[test]
+    fn reports_malformed_header() {
+        let (line, column, kind) = parse_error("name: Test\n  no colon\n---\nr\n");
+
+        assert_eq!((line, column), (2, 3));
+        assert!(matches!(kind, LevelParseErrorKind::MalformedHeader));
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        // A no-break space takes two bytes.
+        let (line, column, kind) = parse_error("name: Test\n\u{a0}drop_chance: 2\n---\nr\n");
+
+        assert_eq!((line, column), (2, 15));
+        assert!(matches!(kind, LevelParseErrorKind::InvalidValue { .. }));
+    }
+
+    #[test]
+    fn reports_unknown_block() {
+        let (line, column, kind) = parse_error("name: Test\n---\nrr\nröx\n");
+
+        assert_eq!((line, column), (4, 2));
+        assert!(matches!(kind, LevelParseErrorKind::UnknownBlock('ö')));
+    }
+
+    #[test]
+    fn reports_missing_key_at_separator() {
+        let (line, column, kind) = parse_error("; No name.\ndrop_chance: 0.5\n---\nr\n");
+
+        assert_eq!((line, column), (3, 1));
+        assert!(matches!(kind, LevelParseErrorKind::MissingKey("name")));
+    }
+
+    #[test]
+    fn reports_max_ball_speed_below_ball_speed() {
+        let (line, column, kind) =
+            parse_error("name: Test\nball_speed: 600\nmax_ball_speed: 500\n---\nr\n");
+
+        assert_eq!((line, column), (3, 17));
+        assert!(matches!(kind, LevelParseErrorKind::MaxBallSpeedTooLow));
+    }
+}
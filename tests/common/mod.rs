@@ -0,0 +1,104 @@
+//! Helpers shared by the integration tests. Not every test uses all of them.
+#![allow(dead_code)]
+
+use std::{thread, time::Duration};
+
+use bevy::{app::PluginGroupBuilder, prelude::*};
+use breakout::{
+    StartGame,
+    app_state::AppState,
+    ball::Ball,
+    headless::HeadlessPlugin,
+    input::{PaddleInput, PlayerInputs, ReadInput, read_devices_system},
+    simulation::SimulationTick,
+};
+
+/// How many updates a test waits for something before it fails. Assets load on other threads, so
+/// this is generous.
+const MAX_UPDATES: usize = 2000;
+
+/// A headless app with the given plugins of the game that ran its startup systems and is updated by
+/// hand from here on.
+pub fn headless_app(plugins: PluginGroupBuilder) -> App {
+    let mut app = App::new();
+    app.add_plugins((HeadlessPlugin::default(), plugins));
+    app.finish();
+    app.cleanup();
+    app.update();
+    app
+}
+
+/// Updates the app until `done` holds, sleeping a little in between so assets can load.
+pub fn update_until(app: &mut App, what: &str, mut done: impl FnMut(&mut World) -> bool) {
+    for _ in 0..MAX_UPDATES {
+        app.update();
+        if done(app.world_mut()) {
+            return;
+        };
+        thread::sleep(Duration::from_millis(1));
+    }
+    panic!("Gave up waiting for {what} after {MAX_UPDATES} updates");
+}
+
+/// Starts the campaign and waits until its first level is played.
+pub fn start_game(app: &mut App) {
+    app.world_mut().trigger(StartGame);
+    update_until(app, "the first level", |world| {
+        *world.resource::<State<AppState>>().get() == AppState::InGame
+    });
+}
+
+/// Plays `script` on the paddle of the first player instead of reading the devices. It gets the
+/// tick the input is for, counted from the start of the level.
+pub fn script_inputs(app: &mut App, script: fn(u64) -> PaddleInput) {
+    app.add_systems(
+        RunFixedMainLoop,
+        (move |tick: Res<SimulationTick>, mut inputs: ResMut<PlayerInputs>| {
+            inputs.0.first = script(tick.0);
+        })
+        .in_set(ReadInput)
+        .after(read_devices_system),
+    );
+}
+
+/// Launches every ball right away and sweeps the paddle from side to side, so the balls hit blocks
+/// and the ball speed and angle keep changing.
+pub fn sweep_and_launch(tick: u64) -> PaddleInput {
+    PaddleInput {
+        axis: if (tick / 90).is_multiple_of(2) {
+            1.0
+        } else {
+            -1.0
+        },
+        target: None,
+        launch: true,
+    }
+}
+
+/// Keeps the paddle of the first player under the lowest ball and launches every ball right away,
+/// so the level goes on without losing lives.
+pub fn follow_ball(app: &mut App) {
+    app.add_systems(
+        RunFixedMainLoop,
+        (|balls: Query<&Transform, With<Ball>>, mut inputs: ResMut<PlayerInputs>| {
+            let lowest = balls
+                .iter()
+                .map(|transform| transform.translation)
+                .min_by(|a, b| a.y.total_cmp(&b.y));
+            inputs.0.first = PaddleInput {
+                axis: 0.0,
+                target: lowest.map(|ball| ball.x),
+                launch: true,
+            };
+        })
+        .in_set(ReadInput)
+        .after(read_devices_system),
+    );
+}
+
+/// Updates the app `ticks` times, which plays as many simulation steps.
+pub fn run_ticks(app: &mut App, ticks: u64) {
+    for _ in 0..ticks {
+        app.update();
+    }
+}
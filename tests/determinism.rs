@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use breakout::{
+    BreakoutPlugins,
+    simulation::{SimulationConfig, SimulationTick},
+    world::block::BlockBreakEvent,
+};
+
+mod common;
+
+const SEED: u64 = 42;
+const TICKS: u64 = 1800;
+
+/// The tick and position of every broken block, in the order they broke.
+#[derive(Debug, Default, Resource)]
+struct Breaks(Vec<(u64, Vec2)>);
+
+fn record_break_observer(
+    trigger: Trigger<BlockBreakEvent>,
+    tick: Res<SimulationTick>,
+    blocks: Query<&Transform>,
+    mut breaks: ResMut<Breaks>,
+) {
+    if let Ok(transform) = blocks.get(trigger.block) {
+        breaks.0.push((tick.0, transform.translation.truncate()));
+    };
+}
+
+fn play(seed: u64) -> Vec<(u64, Vec2)> {
+    let mut app = common::headless_app(BreakoutPlugins.build());
+    app.init_resource::<Breaks>()
+        .add_observer(record_break_observer);
+    app.world_mut().resource_mut::<SimulationConfig>().seed = Some(seed);
+    common::script_inputs(&mut app, common::sweep_and_launch);
+
+    common::start_game(&mut app);
+    common::run_ticks(&mut app, TICKS);
+
+    app.world_mut().remove_resource::<Breaks>().unwrap().0
+}
+
+#[test]
+fn same_seed_and_inputs_break_the_same_blocks() {
+    let first = play(SEED);
+    assert!(!first.is_empty(), "No block broke in {TICKS} ticks");
+
+    assert_eq!(first, play(SEED));
+}
@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+use breakout::{
+    BreakoutPlugins, ball::Ball, score::Score, simulation::SimulationTick, world::block::Block,
+};
+
+mod common;
+
+fn count<C: Component>(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<C>>()
+        .iter(app.world())
+        .count()
+}
+
+#[test]
+fn plays_the_first_level() {
+    let mut app = common::headless_app(BreakoutPlugins.build());
+    common::start_game(&mut app);
+
+    // The levels are embedded, so the first one loaded without an asset folder.
+    let blocks = count::<Block>(&mut app);
+    assert!(blocks > 0, "The first level has no blocks");
+    assert_eq!(count::<Ball>(&mut app), 1);
+    assert_eq!(app.world().resource::<Score>().points, 0);
+
+    // Every update runs exactly one simulation step.
+    let start = app.world().resource::<SimulationTick>().0;
+    common::follow_ball(&mut app);
+    common::run_ticks(&mut app, 1200);
+    assert_eq!(app.world().resource::<SimulationTick>().0, start + 1200);
+
+    assert!(count::<Block>(&mut app) < blocks, "No block broke");
+    assert!(app.world().resource::<Score>().points > 0);
+    assert!(count::<Ball>(&mut app) > 0);
+}
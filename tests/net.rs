@@ -0,0 +1,81 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    thread,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use breakout::{
+    BreakoutPlugins,
+    net::{
+        NetMode, NetPlugin,
+        client::{Client, ClientEvent},
+        host::Host,
+    },
+    world::block::Block,
+};
+
+mod common;
+
+/// Updates the host and the client until the client got an event that `done` accepts.
+fn exchange(
+    host: &mut App,
+    client: &mut Client,
+    what: &str,
+    mut done: impl FnMut(&mut App, &Client, ClientEvent) -> bool,
+) {
+    for _ in 0..2000 {
+        host.update();
+        for event in client.update() {
+            if done(host, client, event) {
+                return;
+            };
+        }
+        client.send_input(0.0, false);
+        thread::sleep(Duration::from_millis(1));
+    }
+    panic!("Gave up waiting for {what}");
+}
+
+#[test]
+fn client_joins_over_loopback() {
+    let mut host = common::headless_app(BreakoutPlugins.set(NetPlugin {
+        mode: NetMode::Host { port: 0 },
+    }));
+    let port = host.world().resource::<Host>().port();
+    assert_ne!(port, 0);
+
+    let mut client = Client::connect(SocketAddr::from((Ipv4Addr::LOCALHOST, port))).unwrap();
+    exchange(&mut host, &mut client, "the welcome", |_, _, event| {
+        matches!(event, ClientEvent::Connected)
+    });
+    assert!(client.is_connected());
+    assert!(host.world().resource::<Host>().peer_address().is_some());
+
+    common::start_game(&mut host);
+    let blocks = host
+        .world_mut()
+        .query_filtered::<(), With<Block>>()
+        .iter(host.world())
+        .count();
+    assert!(blocks > 0, "The first level has no blocks");
+
+    // The snapshots only tell which blocks are left, their layout arrives separately.
+    exchange(&mut host, &mut client, "the blocks", |_, client, event| {
+        let ClientEvent::Snapshot(snapshot) = event else {
+            return false;
+        };
+        client.blocks(&snapshot.blocks).len() == blocks
+    });
+}
+
+#[test]
+fn host_exits_if_the_port_is_taken() {
+    let taken = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+    let port = taken.local_addr().unwrap().port();
+
+    let host = common::headless_app(BreakoutPlugins.set(NetPlugin {
+        mode: NetMode::Host { port },
+    }));
+    assert_eq!(host.should_exit(), Some(AppExit::error()));
+}
@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use breakout::{
+    BreakoutPlugins, game_over::GameOverPlugin, hud::HudPlugin,
+    level_complete::LevelCompletePlugin, main_menu::MainMenuPlugin, net::NetPlugin,
+    pause_menu::PauseMenuPlugin, replay::ReplayPlugin, simulation::SimulationTick, ui::UiPlugin,
+    versus::VersusPlugin, world::block::Block,
+};
+
+mod common;
+
+fn play_a_level(mut app: App) {
+    common::start_game(&mut app);
+
+    let blocks = app
+        .world_mut()
+        .query_filtered::<(), With<Block>>()
+        .iter(app.world())
+        .count();
+    assert!(blocks > 0, "The first level has no blocks");
+
+    let start = app.world().resource::<SimulationTick>().0;
+    for _ in 0..60 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<SimulationTick>().0, start + 60);
+}
+
+#[test]
+fn runs_without_the_menu_and_the_hud() {
+    play_a_level(common::headless_app(
+        BreakoutPlugins
+            .build()
+            .disable::<MainMenuPlugin>()
+            .disable::<HudPlugin>(),
+    ));
+}
+
+#[test]
+fn runs_with_only_the_required_plugins() {
+    play_a_level(common::headless_app(
+        BreakoutPlugins
+            .build()
+            .disable::<UiPlugin>()
+            .disable::<MainMenuPlugin>()
+            .disable::<LevelCompletePlugin>()
+            .disable::<GameOverPlugin>()
+            .disable::<VersusPlugin>()
+            .disable::<HudPlugin>()
+            .disable::<PauseMenuPlugin>()
+            .disable::<NetPlugin>()
+            .disable::<ReplayPlugin>(),
+    ));
+}
@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use breakout::{
+    BreakoutPlugins, StopGame,
+    replay::{ReplayMode, ReplayPlugin, Session},
+    simulation::SimulationConfig,
+};
+
+mod common;
+
+/// A path in the temporary directory that no other test uses.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("breakout-{}-{name}.replay", std::process::id()))
+}
+
+/// Records the first level with scripted input until `ticks` steps were played.
+fn record(path: &Path, ticks: u64) {
+    let mut app = common::headless_app(BreakoutPlugins.set(ReplayPlugin {
+        mode: ReplayMode::Record(path.to_path_buf()),
+    }));
+    app.world_mut().resource_mut::<SimulationConfig>().seed = Some(7);
+    common::script_inputs(&mut app, common::sweep_and_launch);
+
+    common::start_game(&mut app);
+    common::run_ticks(&mut app, ticks);
+
+    // Leaving the level saves it.
+    app.world_mut().trigger(StopGame);
+    app.update();
+}
+
+/// Plays the replay at `path` back until it exits.
+fn play(path: &Path) -> AppExit {
+    let mut app = common::headless_app(BreakoutPlugins.set(ReplayPlugin {
+        mode: ReplayMode::Play(path.to_path_buf()),
+    }));
+
+    let mut exit = None;
+    common::update_until(&mut app, "the end of the replay", |world| {
+        exit = world.resource_mut::<Events<AppExit>>().drain().next();
+        exit.is_some()
+    });
+    exit.unwrap()
+}
+
+#[test]
+fn plays_back_what_it_recorded() {
+    let path = temp_path("recorded");
+    record(&path, 900);
+
+    let session = Session::load(&path).unwrap();
+    assert_eq!(session.levels.len(), 1);
+    assert!(
+        !session.levels[0].breaks.is_empty(),
+        "No block broke while recording"
+    );
+    let played = play(&path);
+
+    // Serving later changes when every block breaks.
+    let mut tampered = session;
+    tampered.levels[0].inputs[0].inputs.first.launch = false;
+    let tampered_path = temp_path("tampered");
+    tampered.save(&tampered_path).unwrap();
+    let tampered_played = play(&tampered_path);
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&tampered_path).unwrap();
+    assert_eq!(played, AppExit::Success);
+    assert_eq!(tampered_played, AppExit::error());
+}